@@ -1,8 +1,9 @@
-use cgmath::{EuclideanSpace, InnerSpace};
 use glium::Surface;
 use imgui::*;
 use imgui_winit_support;
-use imguizmo::{Gizmo, Mode, Operation, Rect};
+use imguizmo::camera::Camera;
+use imguizmo::widgets::{transform_inspector, InspectorState};
+use imguizmo::{Gizmo, Mode, Operation, Projection, Rect};
 use std::time::Instant;
 use winit::{
     event::{Event, WindowEvent},
@@ -51,15 +52,13 @@ fn main() {
         [0.0, 0.0, 0.0, 1.0],
     ];
 
-    let eye = cgmath::Point3::new(8.0, 8.0, 8.0);
-    let center = cgmath::Point3::origin();
-    let up = cgmath::Vector3::unit_y();
-    let mut view: [[f32; 4]; 4] = cgmath::Matrix4::<f32>::look_at(eye, center, up).into();
-    let camera_distance = (eye - center).magnitude();
+    let mut camera = Camera::look_at([8.0, 8.0, 8.0], [0.0, 0.0, 0.0]);
+    let mut view: [[f32; 4]; 4] = camera.view_matrix();
 
     let mut draw_cube = true;
     let mut draw_grid = true;
     let mut is_orthographic = false;
+    let mut orthographic = Projection::Orthographic { view_width: 10.0 };
     let mut operation = Operation::Rotate;
     let mut mode = Mode::Local;
     let mut grid_size = 10.0;
@@ -69,6 +68,7 @@ fn main() {
     let mut bounds_snap = [0.1, 0.1, 0.1];
     let mut bound_sizing = false;
     let mut bound_sizing_snap = false;
+    let mut inspector = InspectorState::new();
 
     event_loop.run(move |event, _, control_flow| {
         match event {
@@ -97,7 +97,10 @@ fn main() {
                     let projection: [[f32; 4]; 4] = if !is_orthographic {
                         cgmath::perspective(cgmath::Deg(65.0), aspect_ratio, 0.01, 1000.0).into()
                     } else {
-                        let view_width = 10.0;
+                        camera.update_projection(&mut orthographic);
+                        let Projection::Orthographic { view_width } = orthographic else {
+                            unreachable!()
+                        };
                         let view_height = view_width * height / width;
                         cgmath::ortho(
                             -view_width,
@@ -131,9 +134,13 @@ fn main() {
                         ui.checkbox("Use snap", &mut use_snap);
                         ui.checkbox("Bound sizing", &mut bound_sizing);
                         ui.checkbox("Bound sizing snap", &mut bound_sizing_snap);
+
+                        ui.new_line();
+                        transform_inspector(ui, &mut cube_model, &mut inspector);
                     });
 
                     let rect = Rect::from_display(&ui);
+                    camera.update(&gizmo, rect, &projection, &mut view);
                     gizmo.set_rect(rect.x, rect.y, rect.width, rect.height);
                     gizmo.set_orthographic(is_orthographic);
                     if draw_cube {
@@ -168,7 +175,7 @@ fn main() {
                     let background_color = 0;
                     gizmo.view_manipulate(
                         &mut view,
-                        camera_distance,
+                        camera.distance,
                         position,
                         size,
                         background_color,
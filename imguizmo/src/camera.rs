@@ -0,0 +1,642 @@
+//! A mouse and keyboard camera controller. See [`Camera`].
+use crate::{math, AsMatrix4, FromMatrix4, Gizmo, Matrix4, Projection, Ray, Rect, Vector3};
+
+use imgui::{Key, MouseButton, Ui};
+use std::f32::consts::FRAC_PI_2;
+
+/// Keep the camera from looking straight up or down, where the up vector is degenerate.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.001;
+
+/// The mouse buttons and keys used by a [`Camera`]. `None` disables a control.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CameraControls {
+    /// Orbit around the target while held.
+    pub orbit: Option<MouseButton>,
+    /// Pan instead of orbiting while held when the orbit button is pressed.
+    pub pan_modifier: Option<Key>,
+    /// Dolly towards the target instead of orbiting while held when the orbit button is
+    /// pressed.
+    pub dolly_modifier: Option<Key>,
+    /// Look around and fly with the movement keys while held.
+    pub fly: Option<MouseButton>,
+    pub forward: Option<Key>,
+    pub backward: Option<Key>,
+    pub left: Option<Key>,
+    pub right: Option<Key>,
+    pub up: Option<Key>,
+    pub down: Option<Key>,
+    /// Fly faster while held.
+    pub fast: Option<Key>,
+}
+
+impl Default for CameraControls {
+    fn default() -> Self {
+        CameraControls {
+            orbit: Some(MouseButton::Middle),
+            pan_modifier: Some(Key::ModShift),
+            dolly_modifier: Some(Key::ModCtrl),
+            fly: Some(MouseButton::Right),
+            forward: Some(Key::W),
+            backward: Some(Key::S),
+            left: Some(Key::A),
+            right: Some(Key::D),
+            up: Some(Key::E),
+            down: Some(Key::Q),
+            fast: Some(Key::ModShift),
+        }
+    }
+}
+
+/// A move of the target and distance over `duration` seconds, started by framing, with the
+/// `view_width` of an orthographic projection going from the first to the second value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Animation {
+    from: (Vector3, f32),
+    to: (Vector3, f32),
+    view_width: Option<(f32, f32)>,
+    elapsed: f32,
+    duration: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Drag {
+    Orbit(MouseButton),
+    Pan(MouseButton),
+    Dolly(MouseButton),
+    Fly(MouseButton),
+}
+
+impl Drag {
+    fn button(self) -> MouseButton {
+        match self {
+            Drag::Orbit(button) | Drag::Pan(button) | Drag::Dolly(button) | Drag::Fly(button) => button,
+        }
+    }
+}
+
+/// A camera orbiting a target point, with the Y axis up. Reads the mouse and keyboard to
+/// orbit, pan, dolly, zoom towards the cursor with the mouse wheel and fly with WASD, and
+/// writes a right-handed view matrix for [`Builder`](crate::Builder) and `view_manipulate`.
+///
+/// Input is only read while the mouse is over the viewport rect and not over the gizmo or
+/// another window, but a drag that started there goes on until its button is released.
+///
+/// The view matrix can be changed by other means, e.g. `view_manipulate`: the camera picks
+/// up the change on the next `update`, with the target `distance` in front of the camera.
+/// Pass the same `distance` to `view_manipulate` so that both orbit the same point.
+///
+/// Orthographic projections are zoomed and framed by changing their `view_width`, which
+/// `update_projection` applies before the projection matrix is built.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4, Projection, Rect};
+/// # use imguizmo::camera::Camera;
+/// # let mut model = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let mut camera = Camera::look_at([8.0, 8.0, 8.0], [0.0, 0.0, 0.0]);
+/// let mut view: Matrix4 = camera.view_matrix();
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// let rect = Rect::from_display(ui);
+/// let mut projection = Projection::Orthographic { view_width: 10.0 };
+/// camera.update_projection(&mut projection);
+/// let projection_matrix: Matrix4 = projection.matrix(&rect, gizmo.framebuffer_scale());
+/// camera.update(&gizmo, rect, &projection_matrix, &mut view);
+///
+/// let viewport = gizmo.viewport(rect, &view, projection);
+/// viewport.builder(&mut model).manipulate();
+/// viewport.view_manipulate(&mut view, camera.distance, [128.0, 128.0], 0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// The point orbited around.
+    pub target: Vector3,
+    /// The distance from the camera to the target.
+    pub distance: f32,
+    /// The rotation around the Y axis in radians. `0` looks down -Z.
+    pub yaw: f32,
+    /// The elevation in radians. Positive values look down on the target.
+    pub pitch: f32,
+    pub controls: CameraControls,
+    /// Radians per pixel dragged when orbiting or looking around. Defaults to `0.01`.
+    pub orbit_speed: f32,
+    /// Pan speed relative to the mouse. At `1.0`, the default, the point under the cursor at
+    /// the target's depth follows the cursor.
+    pub pan_speed: f32,
+    /// The part of the distance zoomed per mouse wheel step. Defaults to `0.1`.
+    pub zoom_speed: f32,
+    /// Zoom towards the point under the cursor instead of the target. Defaults to `true`.
+    pub zoom_to_cursor: bool,
+    /// Units per second when flying. Defaults to `5.0`.
+    pub fly_speed: f32,
+    /// The factor applied to `fly_speed` while the fast key is held. Defaults to `4.0`.
+    pub fast_factor: f32,
+    /// The closest the camera gets to the target. Defaults to `0.01`.
+    pub min_distance: f32,
+    /// The time in seconds it takes to frame bounds. Defaults to `0.0`, which frames them at
+    /// once.
+    pub frame_duration: f32,
+    /// The room left around framed bounds, as a factor of their size. Defaults to `1.1`.
+    pub frame_margin: f32,
+    drag: Option<Drag>,
+    animation: Option<Animation>,
+    /// The orthographic view width of the animation, until `update_projection` takes it.
+    view_width: Option<f32>,
+    /// The orthographic zoom since the last `update_projection`, as a factor of the view width.
+    view_width_factor: f32,
+    /// The last view matrix written, to notice changes made by others.
+    view: Option<Matrix4>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            target: [0.0; 3],
+            distance: 10.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            controls: CameraControls::default(),
+            orbit_speed: 0.01,
+            pan_speed: 1.0,
+            zoom_speed: 0.1,
+            zoom_to_cursor: true,
+            fly_speed: 5.0,
+            fast_factor: 4.0,
+            min_distance: 0.01,
+            frame_duration: 0.0,
+            frame_margin: 1.1,
+            drag: None,
+            animation: None,
+            view_width: None,
+            view_width_factor: 1.0,
+            view: None,
+        }
+    }
+}
+
+impl Camera {
+    pub fn new(target: Vector3, distance: f32, yaw: f32, pitch: f32) -> Self {
+        Camera {
+            target,
+            distance,
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            ..Self::default()
+        }
+    }
+
+    /// A camera at `eye` orbiting `target`.
+    pub fn look_at(eye: Vector3, target: Vector3) -> Self {
+        let mut camera = Camera {
+            target,
+            ..Self::default()
+        };
+        camera.set_eye(&eye);
+        camera
+    }
+
+    /// A camera with the right-handed `view` matrix, orbiting the point `distance` in front of
+    /// it.
+    pub fn from_view(view: &impl AsMatrix4, distance: f32) -> Self {
+        let mut camera = Camera {
+            distance,
+            ..Self::default()
+        };
+        camera.sync(view.as_matrix4());
+        camera
+    }
+
+    pub fn with_controls(mut self, controls: CameraControls) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    pub fn with_zoom_to_cursor(mut self, zoom_to_cursor: bool) -> Self {
+        self.zoom_to_cursor = zoom_to_cursor;
+        self
+    }
+
+    pub fn with_frame_duration(mut self, seconds: f32) -> Self {
+        self.frame_duration = seconds;
+        self
+    }
+
+    /// The position of the camera.
+    pub fn eye(&self) -> Vector3 {
+        math::add(&self.target, &math::scale(&self.back(), self.distance))
+    }
+
+    /// Move the camera to `eye`, keeping the target.
+    pub fn set_eye(&mut self, eye: &Vector3) {
+        let offset = math::sub(eye, &self.target);
+        self.distance = math::length(&offset).max(self.min_distance);
+        self.set_back(&offset);
+    }
+
+    pub fn view_matrix<M: FromMatrix4>(&self) -> M {
+        math::look_at_rh(&self.eye(), &self.target, &[0.0, 1.0, 0.0])
+    }
+
+    /// Returns true while the camera is being dragged, flown or animated.
+    pub fn is_active(&self) -> bool {
+        self.drag.is_some() || self.animation.is_some()
+    }
+
+    /// Move the camera so that the axis-aligned `bounds`, given as `[min, max]`, fill the view
+    /// of `projection` in `rect`, keeping the direction the camera looks in. The camera moves
+    /// over `frame_duration` seconds during the next calls to `update`.
+    ///
+    /// Orthographic projections get a new `view_width`, at once without a `frame_duration`.
+    /// Otherwise it changes along with the camera when `projection` is passed to
+    /// `update_projection` on every frame.
+    ///
+    /// ```rust,no_run
+    /// # use imguizmo::{Gizmo, Matrix4, Projection, Rect};
+    /// # use imguizmo::camera::Camera;
+    /// # use imgui::Key;
+    /// # let models = [Matrix4::default()];
+    /// # let mut view = Matrix4::default();
+    /// # let ui = unsafe { std::mem::uninitialized() };
+    /// let mut camera = Camera::default().with_frame_duration(0.25);
+    /// let mut projection = Projection::Perspective { fovy: 45.0 };
+    ///
+    /// let gizmo = Gizmo::begin_frame(ui);
+    /// let rect = Rect::from_display(ui);
+    /// if ui.is_key_pressed(Key::F) {
+    ///     camera.frame_models(&models, &[[-1.0; 3], [1.0; 3]], &mut projection, &rect);
+    /// }
+    /// camera.update_projection(&mut projection);
+    /// let projection_matrix: Matrix4 = projection.matrix(&rect, gizmo.framebuffer_scale());
+    /// camera.update(&gizmo, rect, &projection_matrix, &mut view);
+    /// ```
+    pub fn frame_bounds(&mut self, bounds: &[Vector3; 2], projection: &mut Projection, rect: &Rect) {
+        let [min, max] = bounds;
+        let center = math::scale(&math::add(min, max), 0.5);
+        let radius = math::length(&math::sub(max, min)) * 0.5 * self.frame_margin;
+        let aspect_ratio = rect.aspect_ratio();
+        let (distance, view_width) = match projection {
+            Projection::Perspective { fovy } => {
+                // The projection's half height at a distance of one is `tan(fovy)`.
+                let tan = fovy.to_radians().tan();
+                let half_angle = tan.atan().min((tan * aspect_ratio).atan());
+                (radius / half_angle.sin(), None)
+            }
+            Projection::Orthographic { view_width } => {
+                let from = *view_width;
+                let to = radius * aspect_ratio.max(1.0);
+                if self.frame_duration <= 0.0 {
+                    *view_width = to;
+                }
+                // The projection only shows `view_width` in front of and behind the camera.
+                (to - radius, Some((from, to)).filter(|_| self.frame_duration > 0.0))
+            }
+        };
+        self.view_width = None;
+        self.view_width_factor = 1.0;
+        self.animation = Some(Animation {
+            from: (self.target, self.distance),
+            to: (center, distance.max(self.min_distance)),
+            view_width,
+            elapsed: 0.0,
+            duration: self.frame_duration,
+        });
+    }
+
+    /// Frame the `models`, each of which covers `local_bounds` in its own space. See
+    /// `frame_bounds`.
+    pub fn frame_models<M: AsMatrix4>(
+        &mut self,
+        models: &[M],
+        local_bounds: &[Vector3; 2],
+        projection: &mut Projection,
+        rect: &Rect,
+    ) {
+        if let Some(bounds) = world_bounds(models, local_bounds) {
+            self.frame_bounds(&bounds, projection, rect);
+        }
+    }
+
+    /// Set the `view_width` of an orthographic `projection` to that of the current framing
+    /// animation and zoom. Call it before computing the projection matrix. Returns true if
+    /// `projection` was changed.
+    pub fn update_projection(&mut self, projection: &mut Projection) -> bool {
+        let factor = std::mem::replace(&mut self.view_width_factor, 1.0);
+        let width = self.view_width.take();
+        match projection {
+            Projection::Orthographic { view_width } => {
+                let width = width.unwrap_or(*view_width) * factor;
+                let changed = *view_width != width;
+                *view_width = width;
+                changed
+            }
+            Projection::Perspective { .. } => false,
+        }
+    }
+
+    /// Read the mouse and keyboard and update `view`, which is rendered with `projection` in
+    /// `rect`. Returns true if `view` was changed.
+    pub fn update(
+        &mut self,
+        gizmo: &Gizmo,
+        rect: Rect,
+        projection: &impl AsMatrix4,
+        view: &mut impl AsMatrix4,
+    ) -> bool {
+        let view = view.as_matrix4_mut();
+        if self.view != Some(*view) {
+            self.animation = None;
+            self.sync(view);
+        }
+        let ui = gizmo.ui();
+        let io = ui.io();
+        let hovered = rect.contains(io.mouse_pos)
+            && !gizmo.is_over()
+            && !gizmo.is_using()
+            && (!io.want_capture_mouse || ui.is_window_hovered());
+
+        if self.drag.is_some_and(|drag| !ui.is_mouse_down(drag.button())) {
+            self.drag = None;
+        }
+        if self.drag.is_none() && hovered {
+            self.drag = self.start_drag(ui);
+        }
+        // The user takes over from an animation.
+        if self.drag.is_some() || (hovered && io.mouse_wheel != 0.0) {
+            self.animation = None;
+        }
+
+        let before = (self.target, self.distance, self.yaw, self.pitch);
+        let [dx, dy] = io.mouse_delta;
+        match self.drag {
+            Some(Drag::Orbit(_)) => self.orbit(dx, dy),
+            Some(Drag::Pan(_)) => {
+                let units = self.pan_speed * units_per_pixel(projection, &rect, self.distance);
+                let (right, up) = (self.right(), self.up());
+                let offset = math::add(&math::scale(&right, -dx * units), &math::scale(&up, dy * units));
+                self.target = math::add(&self.target, &offset);
+            }
+            Some(Drag::Dolly(_)) => self.zoom(-dy / 20.0, None, is_orthographic(projection)),
+            Some(Drag::Fly(_)) => {
+                let eye = self.eye();
+                self.orbit(dx, dy);
+                self.target = math::sub(&eye, &math::scale(&self.back(), self.distance));
+                if !io.want_text_input {
+                    self.fly(ui, io.delta_time);
+                }
+            }
+            None if hovered && io.mouse_wheel != 0.0 => {
+                let ray = if self.zoom_to_cursor {
+                    Ray::from_screen(io.mouse_pos, &rect, view, projection)
+                } else {
+                    None
+                };
+                self.zoom(io.mouse_wheel, ray, is_orthographic(projection));
+            }
+            None => {}
+        }
+        if let Some(animation) = self.animation.as_mut() {
+            animation.elapsed += io.delta_time;
+            let t = if animation.duration > 0.0 {
+                (animation.elapsed / animation.duration).min(1.0)
+            } else {
+                1.0
+            };
+            // Ease in and out.
+            let t = t * t * (3.0 - 2.0 * t);
+            let ((from_target, from_distance), (to_target, to_distance)) = (animation.from, animation.to);
+            self.target = math::add(&from_target, &math::scale(&math::sub(&to_target, &from_target), t));
+            // Interpolate the distance geometrically, so that zooming looks steady.
+            self.distance = from_distance * (to_distance / from_distance).powf(t);
+            if let Some((from, to)) = animation.view_width {
+                self.view_width = Some(from * (to / from).powf(t));
+            }
+            if t >= 1.0 {
+                self.animation = None;
+            }
+        }
+
+        if (self.target, self.distance, self.yaw, self.pitch) == before {
+            return false;
+        }
+        *view = self.view_matrix();
+        self.view = Some(*view);
+        true
+    }
+
+    fn start_drag(&self, ui: &Ui) -> Option<Drag> {
+        let controls = &self.controls;
+        let clicked = |button: Option<MouseButton>| button.filter(|button| ui.is_mouse_clicked(*button));
+        let held = |key: Option<Key>| key.is_some_and(|key| ui.is_key_down(key));
+        if let Some(button) = clicked(controls.fly) {
+            return Some(Drag::Fly(button));
+        }
+        let button = clicked(controls.orbit)?;
+        Some(if held(controls.pan_modifier) {
+            Drag::Pan(button)
+        } else if held(controls.dolly_modifier) {
+            Drag::Dolly(button)
+        } else {
+            Drag::Orbit(button)
+        })
+    }
+
+    fn orbit(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * self.orbit_speed;
+        self.pitch = (self.pitch + dy * self.orbit_speed).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Zoom in by `steps` of `zoom_speed`, towards the point of `ray` at the target's depth
+    /// if there is one. Orthographic projections zoom by shrinking their view width instead
+    /// of moving closer, which only `update_projection` can apply.
+    fn zoom(&mut self, steps: f32, ray: Option<Ray>, orthographic: bool) {
+        let factor = (1.0 - self.zoom_speed).powf(steps);
+        let factor = if orthographic {
+            factor
+        } else {
+            (self.distance * factor).max(self.min_distance) / self.distance
+        };
+        // Scaling the camera position or the view around a point keeps that point under the
+        // cursor.
+        if let Some(point) = ray.and_then(|ray| self.target_plane_hit(&ray)) {
+            let offset = math::sub(&self.target, &point);
+            self.target = math::add(&point, &math::scale(&offset, factor));
+        }
+        if orthographic {
+            self.view_width_factor *= factor;
+        } else {
+            self.distance *= factor;
+        }
+    }
+
+    fn fly(&mut self, ui: &Ui, delta_time: f32) {
+        let controls = &self.controls;
+        let held = |key: Option<Key>| key.is_some_and(|key| ui.is_key_down(key));
+        let axis = |positive: Option<Key>, negative: Option<Key>| {
+            held(positive) as i32 as f32 - held(negative) as i32 as f32
+        };
+        let forward = math::scale(&self.back(), -axis(controls.forward, controls.backward));
+        let right = math::scale(&self.right(), axis(controls.right, controls.left));
+        let up = [0.0, axis(controls.up, controls.down), 0.0];
+        let direction = math::add(&math::add(&forward, &right), &up);
+        let speed = if held(controls.fast) {
+            self.fly_speed * self.fast_factor
+        } else {
+            self.fly_speed
+        };
+        let offset = math::scale(&math::normalize(&direction), speed * delta_time);
+        self.target = math::add(&self.target, &offset);
+    }
+
+    /// Take over the state of a view matrix that was changed by others.
+    fn sync(&mut self, view: &Matrix4) {
+        if let Some(camera) = math::affine_inverse::<Matrix4>(view) {
+            let eye = math::column3(&camera[3]);
+            let back = math::normalize(&math::column3(&camera[2]));
+            self.set_back(&back);
+            self.target = math::sub(&eye, &math::scale(&back, self.distance));
+        }
+        self.view = Some(*view);
+    }
+
+    /// The point of `ray` on the plane through the target facing the camera.
+    fn target_plane_hit(&self, ray: &Ray) -> Option<Vector3> {
+        ray.intersect_plane(&self.target, &self.back()).map(|distance| ray.at(distance))
+    }
+
+    /// The direction from the target to the camera.
+    fn back(&self) -> Vector3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        [cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw]
+    }
+
+    fn set_back(&mut self, back: &Vector3) {
+        let back = math::normalize(back);
+        self.yaw = back[0].atan2(back[2]);
+        self.pitch = back[1].clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    fn right(&self) -> Vector3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        [cos_yaw, 0.0, -sin_yaw]
+    }
+
+    fn up(&self) -> Vector3 {
+        math::cross(&self.back(), &self.right())
+    }
+}
+
+/// The axis-aligned bounds of `local_bounds` transformed by each of the `models`.
+fn world_bounds<M: AsMatrix4>(models: &[M], local_bounds: &[Vector3; 2]) -> Option<[Vector3; 2]> {
+    let corners = (0..8).map(|corner: usize| {
+        [0, 1, 2].map(|axis| local_bounds[(corner >> axis) & 1][axis])
+    });
+    let points = models
+        .iter()
+        .flat_map(|model| corners.clone().map(move |corner| math::transform_point(model, &corner)));
+    points.fold(None, |bounds, point| {
+        let [min, max] = bounds.unwrap_or([point, point]);
+        Some([
+            [0, 1, 2].map(|axis| min[axis].min(point[axis])),
+            [0, 1, 2].map(|axis| max[axis].max(point[axis])),
+        ])
+    })
+}
+
+/// Returns true for orthographic projection matrices, which leave `w` at `1`.
+fn is_orthographic(projection: &impl AsMatrix4) -> bool {
+    projection.as_matrix4()[3][3] != 0.0
+}
+
+/// The world units per pixel at `distance` in front of a camera with `projection`.
+fn units_per_pixel(projection: &impl AsMatrix4, rect: &Rect, distance: f32) -> f32 {
+    let projection = projection.as_matrix4();
+    let scale = 2.0 / (rect.height * projection[1][1]);
+    // Perspective projections copy -z into w.
+    if is_orthographic(projection) {
+        scale
+    } else {
+        scale * distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() <= 1e-4 * b.abs().max(1.0), "{a:?} != {b:?}");
+        }
+    }
+
+    fn view_width(projection: Projection) -> f32 {
+        match projection {
+            Projection::Orthographic { view_width } => view_width,
+            Projection::Perspective { .. } => panic!("{projection:?} is not orthographic"),
+        }
+    }
+
+    #[test]
+    fn orthographic_zoom_scales_the_view_width() {
+        let mut camera = Camera::look_at([0.0, 0.0, 10.0], [0.0; 3]);
+        camera.zoom(2.0, None, true);
+        assert_eq!((camera.target, camera.distance), ([0.0; 3], 10.0));
+
+        let mut projection = Projection::Orthographic { view_width: 10.0 };
+        assert!(camera.update_projection(&mut projection));
+        assert_close(&[view_width(projection)], &[10.0 * 0.9 * 0.9]);
+        // The zoom is applied once.
+        assert!(!camera.update_projection(&mut projection));
+        assert_close(&[view_width(projection)], &[8.1]);
+    }
+
+    #[test]
+    fn orthographic_zoom_keeps_the_point_under_the_cursor() {
+        let mut camera = Camera::look_at([0.0, 0.0, 10.0], [0.0; 3]);
+        let ray = Ray::new([2.0, 1.0, 10.0], [0.0, 0.0, -1.0]);
+        camera.zoom(-1.0, Some(ray), true);
+        // The view grows by 1 / 0.9 around [2, 1, 0], which moves the target away from it.
+        let factor = 1.0 / 0.9;
+        assert_close(&camera.target, &[2.0 - 2.0 * factor, 1.0 - factor, 0.0]);
+        assert_eq!(camera.distance, 10.0);
+    }
+
+    #[test]
+    fn perspective_zoom_moves_the_camera() {
+        let mut camera = Camera::look_at([0.0, 0.0, 10.0], [0.0; 3]);
+        camera.zoom(1.0, None, false);
+        assert_close(&[camera.distance], &[9.0]);
+
+        let mut projection = Projection::Perspective { fovy: 45.0 };
+        assert!(!camera.update_projection(&mut projection));
+    }
+
+    #[test]
+    fn orthographic_framing_animates_the_view_width() {
+        let mut camera = Camera::default().with_frame_duration(1.0);
+        let mut projection = Projection::Orthographic { view_width: 10.0 };
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+        };
+        camera.frame_bounds(&[[-1.0; 3], [1.0; 3]], &mut projection, &rect);
+        assert_eq!(view_width(projection), 10.0);
+        // A zoom from before the framing is dropped.
+        assert!(!camera.update_projection(&mut projection));
+
+        let mut camera = camera.with_frame_duration(0.0);
+        camera.frame_bounds(&[[-1.0; 3], [1.0; 3]], &mut projection, &rect);
+        assert_close(&[view_width(projection)], &[3.0f32.sqrt() * 1.1]);
+    }
+
+    #[test]
+    fn projection_kind_is_read_from_the_matrix() {
+        let orthographic: Matrix4 = crate::orthographic(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+        let perspective: Matrix4 = crate::perspective(45.0, 1.0, 0.1, 100.0);
+        assert!(is_orthographic(&orthographic));
+        assert!(!is_orthographic(&perspective));
+    }
+}
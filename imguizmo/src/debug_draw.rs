@@ -0,0 +1,204 @@
+//! Wireframe shapes for debugging, drawn with ImGui draw lists in a [`Viewport`].
+//!
+//! Lines are clipped to the view frustum like [`Viewport::draw_line_3d`], so shapes that reach
+//! behind the camera are drawn correctly. They go to the viewport's draw target, or behind all
+//! windows without one.
+//!
+//! ```rust,no_run
+//! # use imguizmo::{Gizmo, Matrix4, Projection, Rect};
+//! # use imguizmo::debug_draw::DebugDraw;
+//! # let view = Matrix4::default();
+//! # let model = Matrix4::default();
+//! # let ui = unsafe { std::mem::uninitialized() };
+//! let gizmo = Gizmo::begin_frame(ui);
+//! let viewport = gizmo.viewport(Rect::from_display(ui), &view, Projection::Perspective { fovy: 45.0 });
+//!
+//! let draw = DebugDraw::new(&viewport).with_thickness(2.0);
+//! draw.axis_triad(&model, 1.0);
+//! draw.with_color([1.0, 1.0, 0.0, 1.0]).sphere(&[0.0, 2.0, 0.0], 0.5);
+//! draw.with_color(0xff00ffff).aabb(&[[-1.0; 3], [1.0; 3]]);
+//! ```
+use crate::{math, AsMatrix4, AsVector3, DepthRange, Matrix4, Vector3, Viewport};
+
+use imgui::ImColor32;
+
+const RED: ImColor32 = ImColor32::from_rgb(255, 64, 64);
+const GREEN: ImColor32 = ImColor32::from_rgb(64, 255, 64);
+const BLUE: ImColor32 = ImColor32::from_rgb(64, 64, 255);
+
+/// Draws wireframe shapes in a viewport with a color and line thickness. Use `with_color` and
+/// `with_thickness` to change them for a single shape.
+#[derive(Copy, Clone)]
+pub struct DebugDraw<'v> {
+    viewport: &'v Viewport<'v>,
+    /// Defaults to white.
+    pub color: ImColor32,
+    /// The line thickness in pixels. Defaults to `1.0`.
+    pub thickness: f32,
+    /// The number of line segments of circles. Defaults to `32`.
+    pub segments: usize,
+}
+
+impl<'v> DebugDraw<'v> {
+    pub fn new(viewport: &'v Viewport<'v>) -> Self {
+        DebugDraw {
+            viewport,
+            color: ImColor32::WHITE,
+            thickness: 1.0,
+            segments: 32,
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<ImColor32>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_segments(mut self, segments: usize) -> Self {
+        self.segments = segments.max(3);
+        self
+    }
+
+    pub fn line(&self, a: &impl AsVector3, b: &impl AsVector3) {
+        self.viewport.draw_line_3d(a, b, self.color, self.thickness);
+    }
+
+    /// A circle around `center` in the plane facing `normal`.
+    pub fn circle(&self, center: &impl AsVector3, normal: &impl AsVector3, radius: f32) {
+        let (u, v) = perpendicular(normal.as_vector3());
+        self.ellipse(center.as_vector3(), &math::scale(&u, radius), &math::scale(&v, radius));
+    }
+
+    /// A sphere as three circles around the world axes.
+    pub fn sphere(&self, center: &impl AsVector3, radius: f32) {
+        let center = center.as_vector3();
+        let axes = [[radius, 0.0, 0.0], [0.0, radius, 0.0], [0.0, 0.0, radius]];
+        for (u, v) in [(0, 1), (1, 2), (2, 0)] {
+            self.ellipse(center, &axes[u], &axes[v]);
+        }
+    }
+
+    /// Axis-aligned `bounds`, given as `[min, max]`.
+    pub fn aabb(&self, bounds: &[Vector3; 2]) {
+        self.obb(&math::IDENTITY, bounds);
+    }
+
+    /// The `bounds` `[min, max]` of an object in its own space, placed by its `model` matrix.
+    pub fn obb(&self, model: &impl AsMatrix4, bounds: &[Vector3; 2]) {
+        let corners: [Vector3; 8] = std::array::from_fn(|corner| {
+            let local = [0, 1, 2].map(|axis| bounds[(corner >> axis) & 1][axis]);
+            math::transform_point(model, &local)
+        });
+        self.box_edges(&corners);
+    }
+
+    /// The view frustum of a camera with the given `view` and `projection` matrices, whose
+    /// near and far planes are mapped to `depth_range`, e.g. that of
+    /// [`Projection::depth_range`](crate::Projection::depth_range).
+    pub fn frustum(
+        &self,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
+        depth_range: DepthRange,
+    ) {
+        let view_projection: Matrix4 = math::multiply(projection, view);
+        let inverse: Matrix4 = match math::inverse(&view_projection) {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        let near = match depth_range {
+            DepthRange::NegativeOneToOne => -1.0,
+            DepthRange::ZeroToOne => 0.0,
+        };
+        let corners: [Vector3; 8] = std::array::from_fn(|corner| {
+            let ndc = [0, 1, 2].map(|axis| match ((corner >> axis) & 1, axis) {
+                (0, 2) => near,
+                (0, _) => -1.0,
+                _ => 1.0,
+            });
+            math::transform_point(&inverse, &ndc)
+        });
+        self.box_edges(&corners);
+    }
+
+    /// An arrow from `from` to `to`, with a head a fifth of its length.
+    pub fn arrow(&self, from: &impl AsVector3, to: &impl AsVector3) {
+        let (from, to) = (from.as_vector3(), to.as_vector3());
+        self.line(from, to);
+        let direction = math::sub(to, from);
+        let length = math::length(&direction);
+        if length <= f32::EPSILON {
+            return;
+        }
+        let head = length * 0.2;
+        let base = math::sub(to, &math::scale(&direction, head / length));
+        let (u, v) = perpendicular(&direction);
+        for side in [u, v, math::scale(&u, -1.0), math::scale(&v, -1.0)] {
+            self.line(to, &math::add(&base, &math::scale(&side, head * 0.4)));
+        }
+    }
+
+    /// Arrows of `size` along the X, Y and Z axes of `model`, in red, green and blue.
+    pub fn axis_triad(&self, model: &impl AsMatrix4, size: f32) {
+        let model = model.as_matrix4();
+        let origin = math::column3(&model[3]);
+        for (axis, color) in [RED, GREEN, BLUE].into_iter().enumerate() {
+            let direction = math::normalize(&math::column3(&model[axis]));
+            let tip = math::add(&origin, &math::scale(&direction, size));
+            self.with_color(color).arrow(&origin, &tip);
+        }
+    }
+
+    /// A cone from `apex` along `direction` for `length`, opening by `angle_degrees` from its
+    /// axis, e.g. the light of a spot light.
+    pub fn cone(&self, apex: &impl AsVector3, direction: &impl AsVector3, length: f32, angle_degrees: f32) {
+        let apex = apex.as_vector3();
+        let direction = math::normalize(direction.as_vector3());
+        let center = math::add(apex, &math::scale(&direction, length));
+        let radius = length * angle_degrees.to_radians().tan();
+        let (u, v) = perpendicular(&direction);
+        let (u, v) = (math::scale(&u, radius), math::scale(&v, radius));
+        self.ellipse(&center, &u, &v);
+        for side in [u, v, math::scale(&u, -1.0), math::scale(&v, -1.0)] {
+            self.line(apex, &math::add(&center, &side));
+        }
+    }
+
+    /// The closed curve `center + cos(t) * u + sin(t) * v`.
+    fn ellipse(&self, center: &Vector3, u: &Vector3, v: &Vector3) {
+        let point = |segment: usize| {
+            let angle = segment as f32 / self.segments as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            math::add(center, &math::add(&math::scale(u, cos), &math::scale(v, sin)))
+        };
+        for segment in 0..self.segments {
+            self.line(&point(segment), &point(segment + 1));
+        }
+    }
+
+    /// The 12 edges of a box whose corners are indexed by their X, Y and Z bits.
+    fn box_edges(&self, corners: &[Vector3; 8]) {
+        for corner in 0..8 {
+            for axis in 0..3 {
+                let other = corner | (1 << axis);
+                if other != corner {
+                    self.line(&corners[corner], &corners[other]);
+                }
+            }
+        }
+    }
+}
+
+/// Two unit vectors perpendicular to `normal` and to each other.
+fn perpendicular(normal: &Vector3) -> (Vector3, Vector3) {
+    let normal = math::normalize(normal);
+    let other = if normal[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    let u = math::normalize(&math::cross(&normal, &other));
+    let v = math::cross(&normal, &u);
+    (u, v)
+}
@@ -0,0 +1,662 @@
+//! Pure-Rust matrix decomposition with selectable rotation conventions.
+//!
+//! ```rust
+//! use imguizmo::decompose::{decompose, recompose, AngleUnit, EulerOrder, RotationFormat};
+//! # use imguizmo::Matrix4;
+//!
+//! let model: Matrix4 = [
+//!     [0.0, 1.0, 0.0, 0.0],
+//!     [-1.0, 0.0, 0.0, 0.0],
+//!     [0.0, 0.0, 1.0, 0.0],
+//!     [1.0, 2.0, 3.0, 1.0],
+//! ];
+//! let components = decompose(&model, RotationFormat::Euler(EulerOrder::ZYX, AngleUnit::Degrees));
+//! assert_eq!(components.translation, [1.0, 2.0, 3.0]);
+//!
+//! let matrix: Matrix4 = recompose(&components.translation, &components.rotation, &components.scale);
+//! # for (a, b) in matrix.iter().flatten().zip(model.iter().flatten()) {
+//! #     assert!((a - b).abs() < 1e-6);
+//! # }
+//! ```
+use crate::math;
+use crate::{AsMatrix4, FromMatrix4, Matrix4, Vector3};
+
+/// A unit quaternion stored as `[x, y, z, w]`.
+pub type Quaternion = [f32; 4];
+
+/// A column-major 3x3 rotation matrix.
+type Matrix3 = [Vector3; 3];
+
+/// The order in which Euler angles are applied, about the fixed world axes. `XYZ` rotates
+/// around X first and Z last, i.e. `R = Rz * Ry * Rx`, which is the convention used by ImGuizmo.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EulerOrder {
+    #[default]
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    /// The axis indices in the order they are applied.
+    fn axes(self) -> [usize; 3] {
+        match self {
+            EulerOrder::XYZ => [0, 1, 2],
+            EulerOrder::XZY => [0, 2, 1],
+            EulerOrder::YXZ => [1, 0, 2],
+            EulerOrder::YZX => [1, 2, 0],
+            EulerOrder::ZXY => [2, 0, 1],
+            EulerOrder::ZYX => [2, 1, 0],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+impl AngleUnit {
+    pub fn to_radians(self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => angle.to_radians(),
+            AngleUnit::Radians => angle,
+        }
+    }
+
+    pub fn from_radians(self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => angle.to_degrees(),
+            AngleUnit::Radians => angle,
+        }
+    }
+}
+
+/// The representation a rotation is decomposed into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RotationFormat {
+    Euler(EulerOrder, AngleUnit),
+    Quaternion,
+    AxisAngle(AngleUnit),
+}
+
+impl Default for RotationFormat {
+    /// XYZ Euler angles in degrees, matching ImGuizmo.
+    fn default() -> Self {
+        RotationFormat::Euler(EulerOrder::XYZ, AngleUnit::Degrees)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rotation {
+    /// Euler angles given as `[x, y, z]`, regardless of the order they are applied in.
+    Euler {
+        angles: Vector3,
+        order: EulerOrder,
+        unit: AngleUnit,
+    },
+    Quaternion(Quaternion),
+    /// A rotation of `angle` around the unit vector `axis`.
+    AxisAngle {
+        axis: Vector3,
+        angle: f32,
+        unit: AngleUnit,
+    },
+}
+
+impl Rotation {
+    pub fn to_quaternion(self) -> Quaternion {
+        match self {
+            Rotation::Quaternion(q) => normalize_quaternion(q),
+            Rotation::AxisAngle { axis, angle, unit } => {
+                let len = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+                if len <= f32::EPSILON {
+                    return [0.0, 0.0, 0.0, 1.0];
+                }
+                let (s, c) = (unit.to_radians(angle) * 0.5).sin_cos();
+                let s = s / len;
+                [axis[0] * s, axis[1] * s, axis[2] * s, c]
+            }
+            Rotation::Euler { .. } => quaternion_from_matrix3(&self.to_matrix3()),
+        }
+    }
+
+    /// Converts to another representation of the same rotation.
+    pub fn to_format(self, format: RotationFormat) -> Rotation {
+        match format {
+            RotationFormat::Quaternion => Rotation::Quaternion(self.to_quaternion()),
+            RotationFormat::AxisAngle(unit) => axis_angle_from_quaternion(self.to_quaternion(), unit),
+            RotationFormat::Euler(order, unit) => euler_from_matrix3(&self.to_matrix3(), order, unit),
+        }
+    }
+
+    /// The rotation as a 4x4 matrix.
+    pub fn to_matrix<M: FromMatrix4>(self) -> M {
+        M::from_matrix4(match self {
+            Rotation::Euler {
+                angles,
+                order,
+                unit,
+            } => order.axes().iter().fold(math::IDENTITY, |m, &axis| {
+                math::multiply(&math::rotation_axis(axis, unit.to_radians(angles[axis])), &m)
+            }),
+            _ => {
+                let r = matrix3_from_quaternion(self.to_quaternion());
+                [
+                    [r[0][0], r[0][1], r[0][2], 0.0],
+                    [r[1][0], r[1][1], r[1][2], 0.0],
+                    [r[2][0], r[2][1], r[2][2], 0.0],
+                    [0.0, 0.0, 0.0, 1.0],
+                ]
+            }
+        })
+    }
+
+    fn to_matrix3(self) -> Matrix3 {
+        let m: Matrix4 = self.to_matrix();
+        [0, 1, 2].map(|axis| math::column3(&m[axis]))
+    }
+}
+
+/// Translation, rotation and scale of a matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Components {
+    pub translation: Vector3,
+    pub rotation: Rotation,
+    pub scale: Vector3,
+}
+
+/// Decompose `matrix` into translation, rotation and scale. A mirrored matrix is returned as a
+/// negative X scale so that the rotation stays proper.
+///
+/// Euler angles in gimbal lock, where the middle rotation is ±90°, put the whole rotation
+/// around the shared axis into the first angle and return `0` for the last one. ImGuizmo and
+/// [`decompose_matrix_to_components`](crate::decompose_matrix_to_components) return angles
+/// that depend on rounding errors there instead.
+pub fn decompose(matrix: &impl AsMatrix4, format: RotationFormat) -> Components {
+    let (translation, mut r, mut scale) = split(matrix.as_matrix4());
+    if determinant3(&r) < 0.0 {
+        scale[0] = -scale[0];
+        r[0] = [-r[0][0], -r[0][1], -r[0][2]];
+    }
+    let rotation = match format {
+        RotationFormat::Euler(order, unit) => euler_from_matrix3(&r, order, unit),
+        RotationFormat::Quaternion => Rotation::Quaternion(quaternion_from_matrix3(&r)),
+        RotationFormat::AxisAngle(unit) => {
+            axis_angle_from_quaternion(quaternion_from_matrix3(&r), unit)
+        }
+    };
+    Components {
+        translation,
+        rotation,
+        scale,
+    }
+}
+
+/// Compose a matrix from translation, rotation and scale. Scales of zero are replaced with
+/// `0.001` so that the result stays invertible, as ImGuizmo does.
+pub fn recompose<M: FromMatrix4>(translation: &Vector3, rotation: &Rotation, scale: &Vector3) -> M {
+    let r = rotation.to_matrix3();
+    let mut m: Matrix4 = [[0.0; 4]; 4];
+    for axis in 0..3 {
+        let scale = if scale[axis].abs() < f32::EPSILON {
+            0.001
+        } else {
+            scale[axis]
+        };
+        m[axis] = [
+            r[axis][0] * scale,
+            r[axis][1] * scale,
+            r[axis][2] * scale,
+            0.0,
+        ];
+    }
+    m[3] = [translation[0], translation[1], translation[2], 1.0];
+    M::from_matrix4(m)
+}
+
+/// ImGuizmo's `DecomposeMatrixToComponents`: XYZ Euler angles in degrees, with its formulas.
+/// Unlike `decompose`, mirrored matrices are not handled and there is no special case for
+/// gimbal lock, so close to ±90° around Y the X and Z angles come from rounding errors.
+pub(crate) fn decompose_imguizmo(matrix: &Matrix4) -> (Vector3, Vector3, Vector3) {
+    let (translation, r, scale) = split(matrix);
+    let rotation = [
+        r[1][2].atan2(r[2][2]),
+        (-r[0][2]).atan2((r[1][2] * r[1][2] + r[2][2] * r[2][2]).sqrt()),
+        r[0][1].atan2(r[0][0]),
+    ];
+    (translation, rotation.map(f32::to_degrees), scale)
+}
+
+/// Split off the translation and the scale of the basis vectors. The basis vectors are
+/// normalized the way ImGuizmo does it, without orthogonalizing them.
+fn split(m: &Matrix4) -> (Vector3, Matrix3, Vector3) {
+    let mut r = [[0.0; 3]; 3];
+    let mut scale = [0.0; 3];
+    for axis in 0..3 {
+        let [x, y, z, _] = m[axis];
+        scale[axis] = (x * x + y * y + z * z).sqrt();
+        let inv = 1.0 / scale[axis].max(f32::EPSILON);
+        r[axis] = [x * inv, y * inv, z * inv];
+    }
+    ([m[3][0], m[3][1], m[3][2]], r, scale)
+}
+
+fn determinant3(r: &Matrix3) -> f32 {
+    r[0][0] * (r[1][1] * r[2][2] - r[2][1] * r[1][2]) - r[1][0] * (r[0][1] * r[2][2] - r[2][1] * r[0][2])
+        + r[2][0] * (r[0][1] * r[1][2] - r[1][1] * r[0][2])
+}
+
+fn euler_from_matrix3(r: &Matrix3, order: EulerOrder, unit: AngleUnit) -> Rotation {
+    // Element at `row`, `column`.
+    let at = |row: usize, column: usize| r[column][row];
+    let [i, j, k] = order.axes();
+    // Odd permutations of XYZ flip the signs of the sine terms.
+    let sign = if j == (i + 1) % 3 { 1.0 } else { -1.0 };
+
+    let cos_j = (at(k, j) * at(k, j) + at(k, k) * at(k, k)).sqrt();
+    let mut angles = [0.0; 3];
+    angles[j] = (-sign * at(k, i)).atan2(cos_j);
+    if cos_j > 16.0 * f32::EPSILON {
+        angles[i] = (sign * at(k, j)).atan2(at(k, k));
+        angles[k] = (sign * at(j, i)).atan2(at(i, i));
+    } else {
+        // Gimbal lock: the first and last rotations share an axis, put it all in the first.
+        angles[i] = (-sign * at(j, k)).atan2(at(j, j));
+        angles[k] = 0.0;
+    }
+
+    Rotation::Euler {
+        angles: angles.map(|angle| unit.from_radians(angle)),
+        order,
+        unit,
+    }
+}
+
+fn quaternion_from_matrix3(r: &Matrix3) -> Quaternion {
+    let at = |row: usize, column: usize| r[column][row];
+    let trace = at(0, 0) + at(1, 1) + at(2, 2);
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            (at(2, 1) - at(1, 2)) / s,
+            (at(0, 2) - at(2, 0)) / s,
+            (at(1, 0) - at(0, 1)) / s,
+            0.25 * s,
+        ]
+    } else if at(0, 0) > at(1, 1) && at(0, 0) > at(2, 2) {
+        let s = (1.0 + at(0, 0) - at(1, 1) - at(2, 2)).sqrt() * 2.0;
+        [
+            0.25 * s,
+            (at(0, 1) + at(1, 0)) / s,
+            (at(0, 2) + at(2, 0)) / s,
+            (at(2, 1) - at(1, 2)) / s,
+        ]
+    } else if at(1, 1) > at(2, 2) {
+        let s = (1.0 + at(1, 1) - at(0, 0) - at(2, 2)).sqrt() * 2.0;
+        [
+            (at(0, 1) + at(1, 0)) / s,
+            0.25 * s,
+            (at(1, 2) + at(2, 1)) / s,
+            (at(0, 2) - at(2, 0)) / s,
+        ]
+    } else {
+        let s = (1.0 + at(2, 2) - at(0, 0) - at(1, 1)).sqrt() * 2.0;
+        [
+            (at(0, 2) + at(2, 0)) / s,
+            (at(1, 2) + at(2, 1)) / s,
+            0.25 * s,
+            (at(1, 0) - at(0, 1)) / s,
+        ]
+    };
+    normalize_quaternion(q)
+}
+
+fn matrix3_from_quaternion(q: Quaternion) -> Matrix3 {
+    let [x, y, z, w] = q;
+    [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + z * w),
+            2.0 * (x * z - y * w),
+        ],
+        [
+            2.0 * (x * y - z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + x * w),
+        ],
+        [
+            2.0 * (x * z + y * w),
+            2.0 * (y * z - x * w),
+            1.0 - 2.0 * (x * x + y * y),
+        ],
+    ]
+}
+
+fn axis_angle_from_quaternion(q: Quaternion, unit: AngleUnit) -> Rotation {
+    // Use the shorter of the two equivalent rotations.
+    let [x, y, z, w] = if q[3] < 0.0 { q.map(|v| -v) } else { q };
+    let sin_half = (x * x + y * y + z * z).sqrt();
+    let angle = 2.0 * sin_half.atan2(w);
+    let axis = if sin_half > f32::EPSILON {
+        [x / sin_half, y / sin_half, z / sin_half]
+    } else {
+        [1.0, 0.0, 0.0]
+    };
+    Rotation::AxisAngle {
+        axis,
+        angle: unit.from_radians(angle),
+        unit,
+    }
+}
+
+pub(crate) fn normalize_quaternion(q: Quaternion) -> Quaternion {
+    let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    if len <= f32::EPSILON {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        q.map(|v| v / len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decompose_matrix_to_components, ffi, recompose_matrix_from_components};
+
+    const SAMPLES: usize = 1000;
+
+    const ORDERS: [EulerOrder; 6] = [
+        EulerOrder::XYZ,
+        EulerOrder::XZY,
+        EulerOrder::YXZ,
+        EulerOrder::YZX,
+        EulerOrder::ZXY,
+        EulerOrder::ZYX,
+    ];
+
+    /// A xorshift generator, so that the samples are the same on every run.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 40) as f32 / (1u64 << 24) as f32
+        }
+
+        fn range(&mut self, min: f32, max: f32) -> f32 {
+            min + (max - min) * self.next()
+        }
+
+        fn vector(&mut self, min: f32, max: f32) -> Vector3 {
+            [
+                self.range(min, max),
+                self.range(min, max),
+                self.range(min, max),
+            ]
+        }
+
+        /// A scale away from zero, negative on some axes.
+        fn scale(&mut self) -> Vector3 {
+            [0, 1, 2].map(|_| {
+                let scale = self.range(0.1, 10.0);
+                if self.next() < 0.2 {
+                    -scale
+                } else {
+                    scale
+                }
+            })
+        }
+
+        /// An affine matrix that is neither orthogonal nor normalized.
+        fn affine(&mut self) -> Matrix4 {
+            let mut matrix = [0, 1, 2, 3].map(|_| {
+                let [x, y, z] = self.vector(-10.0, 10.0);
+                [x, y, z, 0.0]
+            });
+            matrix[3][3] = 1.0;
+            matrix
+        }
+    }
+
+    /// ImGuizmo's `DecomposeMatrixToComponents`.
+    fn imguizmo_decompose(matrix: &Matrix4) -> (Vector3, Vector3, Vector3) {
+        let (mut translation, mut rotation, mut scale) = ([0.0; 3], [0.0; 3], [0.0; 3]);
+        unsafe {
+            ffi::ImGuizmo_DecomposeMatrixToComponents(
+                matrix.as_ptr() as *const f32,
+                translation.as_mut_ptr(),
+                rotation.as_mut_ptr(),
+                scale.as_mut_ptr(),
+            );
+        }
+        (translation, rotation, scale)
+    }
+
+    /// ImGuizmo's `RecomposeMatrixFromComponents`.
+    fn imguizmo_recompose(translation: &Vector3, rotation: &Vector3, scale: &Vector3) -> Matrix4 {
+        let mut matrix = [[0.0; 4]; 4];
+        unsafe {
+            ffi::ImGuizmo_RecomposeMatrixFromComponents(
+                translation.as_ptr(),
+                rotation.as_ptr(),
+                scale.as_ptr(),
+                matrix.as_mut_ptr() as *mut f32,
+            );
+        }
+        matrix
+    }
+
+    /// The rotation of Euler `degrees` applied in `order`, from ImGuizmo's single axis
+    /// rotations.
+    fn imguizmo_euler(degrees: &Vector3, order: EulerOrder) -> Matrix4 {
+        order.axes().iter().fold(math::IDENTITY, |matrix, &axis| {
+            let mut angles = [0.0; 3];
+            angles[axis] = degrees[axis];
+            math::multiply(&imguizmo_recompose(&[0.0; 3], &angles, &[1.0; 3]), &matrix)
+        })
+    }
+
+    fn assert_close(a: &[f32], b: &[f32], tolerance: f32) {
+        for (a, b) in a.iter().zip(b) {
+            assert!(
+                (a - b).abs() <= tolerance * b.abs().max(1.0),
+                "{a:?} != {b:?}",
+            );
+        }
+    }
+
+    fn assert_matrix_close(a: &Matrix4, b: &Matrix4) {
+        assert_close(a.as_flattened(), b.as_flattened(), 1e-4);
+    }
+
+    fn euler_angles(rotation: Rotation) -> Vector3 {
+        match rotation {
+            Rotation::Euler { angles, .. } => angles,
+            rotation => panic!("{rotation:?} is not an Euler rotation"),
+        }
+    }
+
+    #[test]
+    fn recompose_matrix_from_components_matches_imguizmo() {
+        let mut random = Random(1);
+        for _ in 0..SAMPLES {
+            let translation = random.vector(-100.0, 100.0);
+            let rotation = random.vector(-360.0, 360.0);
+            let scale = random.scale();
+            let mut matrix = [[0.0; 4]; 4];
+            recompose_matrix_from_components(&translation, &rotation, &scale, &mut matrix);
+            assert_matrix_close(
+                &matrix,
+                &imguizmo_recompose(&translation, &rotation, &scale),
+            );
+        }
+        // Zero scales are replaced so that the matrix stays invertible.
+        let mut matrix = [[0.0; 4]; 4];
+        recompose_matrix_from_components(&[0.0; 3], &[0.0; 3], &[0.0, 1.0, 2.0], &mut matrix);
+        assert_matrix_close(
+            &matrix,
+            &imguizmo_recompose(&[0.0; 3], &[0.0; 3], &[0.0, 1.0, 2.0]),
+        );
+    }
+
+    #[test]
+    fn decompose_matrix_to_components_matches_imguizmo() {
+        let mut random = Random(2);
+        for sample in 0..SAMPLES {
+            // Rotations away from gimbal lock, where the angles are ill-conditioned, and
+            // arbitrary affine matrices.
+            let matrix = if sample % 2 == 0 {
+                let mut rotation = random.vector(-179.0, 179.0);
+                rotation[1] = random.range(-80.0, 80.0);
+                imguizmo_recompose(&random.vector(-100.0, 100.0), &rotation, &random.scale())
+            } else {
+                random.affine()
+            };
+            let (mut translation, mut rotation, mut scale) = ([0.0; 3], [0.0; 3], [0.0; 3]);
+            decompose_matrix_to_components(&matrix, &mut translation, &mut rotation, &mut scale);
+            let expected = imguizmo_decompose(&matrix);
+            assert_eq!(translation, expected.0);
+            assert_close(&rotation, &expected.1, 1e-3);
+            assert_close(&scale, &expected.2, 1e-5);
+        }
+    }
+
+    #[test]
+    fn euler_round_trips_in_every_order_and_unit() {
+        let mut random = Random(3);
+        for order in ORDERS {
+            let middle = order.axes()[1];
+            for unit in [AngleUnit::Degrees, AngleUnit::Radians] {
+                for _ in 0..SAMPLES {
+                    // Stay clear of gimbal lock, where the angles are not unique.
+                    let mut degrees = random.vector(-179.0, 179.0);
+                    degrees[middle] = random.range(-80.0, 80.0);
+                    let angles = degrees.map(|angle| unit.from_radians(angle.to_radians()));
+                    let rotation = Rotation::Euler {
+                        angles,
+                        order,
+                        unit,
+                    };
+                    assert_matrix_close(&rotation.to_matrix(), &imguizmo_euler(&degrees, order));
+
+                    let (translation, scale) =
+                        (random.vector(-100.0, 100.0), random.vector(0.1, 10.0));
+                    let matrix: Matrix4 = recompose(&translation, &rotation, &scale);
+                    let components = decompose(&matrix, RotationFormat::Euler(order, unit));
+                    assert_close(&components.translation, &translation, 1e-5);
+                    assert_close(&components.scale, &scale, 1e-4);
+                    let decomposed =
+                        euler_angles(components.rotation).map(|angle| unit.to_radians(angle));
+                    assert_close(&decomposed, &degrees.map(f32::to_radians), 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn xyz_degrees_match_imguizmo_outside_of_gimbal_lock() {
+        let mut random = Random(4);
+        for _ in 0..SAMPLES {
+            let mut rotation = random.vector(-179.0, 179.0);
+            rotation[1] = random.range(-80.0, 80.0);
+            let matrix = imguizmo_recompose(
+                &random.vector(-100.0, 100.0),
+                &rotation,
+                &random.vector(0.1, 10.0),
+            );
+            let components = decompose(&matrix, RotationFormat::default());
+            let (translation, expected_rotation, scale) = imguizmo_decompose(&matrix);
+            assert_eq!(components.translation, translation);
+            assert_close(&euler_angles(components.rotation), &expected_rotation, 1e-3);
+            assert_close(&components.scale, &scale, 1e-5);
+        }
+    }
+
+    #[test]
+    fn gimbal_lock_is_stable_unlike_imguizmo() {
+        let mut random = Random(5);
+        for _ in 0..SAMPLES {
+            let mut rotation = random.vector(-179.0, 179.0);
+            rotation[1] = if random.next() < 0.5 { 90.0 } else { -90.0 };
+            let matrix = imguizmo_recompose(&[0.0; 3], &rotation, &[1.0; 3]);
+
+            // `decompose` moves the whole rotation around the shared axis into X, while
+            // ImGuizmo divides by `cos(y)`, which is only a rounding error away from zero.
+            let components = decompose(&matrix, RotationFormat::default());
+            let angles = euler_angles(components.rotation);
+            assert_eq!(angles[2], 0.0);
+            assert_close(&[angles[1]], &[rotation[1]], 1e-2);
+            assert_matrix_close(&imguizmo_recompose(&[0.0; 3], &angles, &[1.0; 3]), &matrix);
+            assert_close(&[imguizmo_decompose(&matrix).1[1]], &[rotation[1]], 1e-2);
+        }
+    }
+
+    #[test]
+    fn quaternion_round_trips() {
+        let mut random = Random(6);
+        for _ in 0..SAMPLES {
+            let (translation, rotation, scale) = (
+                random.vector(-100.0, 100.0),
+                random.vector(-360.0, 360.0),
+                random.vector(0.1, 10.0),
+            );
+            let matrix = imguizmo_recompose(&translation, &rotation, &scale);
+            let components = decompose(&matrix, RotationFormat::Quaternion);
+            let q = match components.rotation {
+                Rotation::Quaternion(q) => q,
+                rotation => panic!("{rotation:?} is not a quaternion"),
+            };
+            assert_close(&[q.iter().map(|v| v * v).sum::<f32>()], &[1.0], 1e-5);
+            assert_matrix_close(
+                &recompose(
+                    &components.translation,
+                    &components.rotation,
+                    &components.scale,
+                ),
+                &matrix,
+            );
+            // Euler angles close to gimbal lock lose some precision on the way.
+            let euler = components.rotation.to_format(RotationFormat::default());
+            let recomposed = imguizmo_recompose(&translation, &euler_angles(euler), &scale);
+            assert_close(recomposed.as_flattened(), matrix.as_flattened(), 1e-3);
+        }
+    }
+
+    #[test]
+    fn axis_angle_round_trips() {
+        let mut random = Random(7);
+        for unit in [AngleUnit::Degrees, AngleUnit::Radians] {
+            for _ in 0..SAMPLES {
+                let (translation, rotation, scale) = (
+                    random.vector(-100.0, 100.0),
+                    random.vector(-360.0, 360.0),
+                    random.vector(0.1, 10.0),
+                );
+                let matrix = imguizmo_recompose(&translation, &rotation, &scale);
+                let components = decompose(&matrix, RotationFormat::AxisAngle(unit));
+                let (axis, angle) = match components.rotation {
+                    Rotation::AxisAngle { axis, angle, .. } => (axis, unit.to_radians(angle)),
+                    rotation => panic!("{rotation:?} is not an axis and angle"),
+                };
+                assert_close(&[math::length(&axis)], &[1.0], 1e-5);
+                assert!((0.0..=std::f32::consts::PI + 1e-5).contains(&angle));
+
+                // The axis is kept in place, and ImGuizmo's matrix agrees on the rotation.
+                let r: Matrix4 = components.rotation.to_matrix();
+                assert_close(&math::transform_vector::<Vector3>(&r, &axis), &axis, 1e-4);
+                assert_matrix_close(
+                    &recompose(&translation, &components.rotation, &scale),
+                    &matrix,
+                );
+            }
+        }
+    }
+}
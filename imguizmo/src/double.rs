@@ -0,0 +1,223 @@
+//! Double-precision model matrices for large worlds.
+//!
+//! ImGuizmo works in `f32`, which makes objects far away from the origin jitter while they are
+//! manipulated. [`manipulate`] rebases the view and model matrices to a camera-relative origin
+//! in `f64`, runs the `f32` gizmo close to the origin and applies the resulting delta back in
+//! `f64`.
+use crate::math::{invert, product, rotation_axis, translation};
+use crate::{AsMatrix4, Gizmo, Matrix4, Mode, Operation, Vector3};
+
+pub type Vector3d = [f64; 3];
+pub type Vector4d = [f64; 4];
+pub type Matrix4d = [Vector4d; 4];
+
+/// Render a gizmo for manipulating a double-precision transformation. Returns true if `model`
+/// was changed. `delta_matrix` receives the change in world space. See
+/// [`Gizmo::manipulate`](../struct.Gizmo.html#method.manipulate).
+#[allow(clippy::too_many_arguments)]
+pub fn manipulate(
+    gizmo: &Gizmo,
+    view: &Matrix4d,
+    projection: &impl AsMatrix4,
+    operation: Operation,
+    mode: Mode,
+    model: &mut Matrix4d,
+    delta_matrix: Option<&mut Matrix4d>,
+    snap: Option<&mut Vector3>,
+    local_bounds: Option<&mut [Vector3; 2]>,
+    bounds_snap: Option<&mut Vector3>,
+) -> bool {
+    let rebased = Rebased::new(view, model);
+    let before = to_f32(&rebased.model);
+    let mut after = before;
+    let changed = crate::manipulate(
+        gizmo,
+        &to_f32(&rebased.view),
+        projection.as_matrix4(),
+        operation as i32,
+        mode,
+        &mut after,
+        None,
+        snap,
+        local_bounds,
+        bounds_snap,
+    );
+    let delta = if changed {
+        rebased.apply(&before, &after, model)
+    } else {
+        None
+    };
+    if let Some(delta_matrix) = delta_matrix {
+        *delta_matrix = delta.unwrap_or_else(|| translation(&[0.0; 3]));
+    }
+    changed
+}
+
+/// The view and model matrices relative to the camera position `origin`, where `f32` is
+/// precise enough for ImGuizmo.
+struct Rebased {
+    origin: Vector3d,
+    view: Matrix4d,
+    model: Matrix4d,
+}
+
+impl Rebased {
+    fn new(view: &Matrix4d, model: &Matrix4d) -> Self {
+        let origin = camera_position(view);
+        Rebased {
+            origin,
+            view: product(view, &translation(&origin)),
+            model: product(&translation(&negate(origin)), model),
+        }
+    }
+
+    /// Apply the change from `before` to `after` of the relative model matrix to `model` and
+    /// return it in world space, or `None` if `before` is singular.
+    fn apply(&self, before: &Matrix4, after: &Matrix4, model: &mut Matrix4d) -> Option<Matrix4d> {
+        let delta = product(&to_f64(after), &invert(&to_f64(before))?);
+        let to_world = translation(&self.origin);
+        *model = product(&to_world, &product(&delta, &self.model));
+        Some(product(&to_world, &product(&delta, &translation(&negate(self.origin)))))
+    }
+}
+
+/// Helper function to decompose a double-precision matrix into its components. Matches
+/// [`decompose_matrix_to_components`](../fn.decompose_matrix_to_components.html): the rotation
+/// is returned as XYZ Euler angles in degrees.
+pub fn decompose_matrix_to_components(
+    matrix: &Matrix4d,
+    translation: &mut Vector3d,
+    rotation: &mut Vector3d,
+    scale: &mut Vector3d,
+) {
+    let mut m = *matrix;
+    for (axis, scale) in scale.iter_mut().enumerate() {
+        *scale = length(&m[axis]);
+        for value in &mut m[axis][..3] {
+            *value /= scale.max(f64::EPSILON);
+        }
+    }
+
+    rotation[0] = m[1][2].atan2(m[2][2]).to_degrees();
+    rotation[1] = (-m[0][2])
+        .atan2((m[1][2] * m[1][2] + m[2][2] * m[2][2]).sqrt())
+        .to_degrees();
+    rotation[2] = m[0][1].atan2(m[0][0]).to_degrees();
+
+    translation.copy_from_slice(&m[3][..3]);
+}
+
+/// Helper function to compose a double-precision matrix from it's components. Matches
+/// [`recompose_matrix_from_components`](../fn.recompose_matrix_from_components.html).
+pub fn recompose_matrix_from_components(
+    translation: &Vector3d,
+    rotation: &Vector3d,
+    scale: &Vector3d,
+    matrix: &mut Matrix4d,
+) {
+    let x = rotation_axis(0, rotation[0].to_radians());
+    let y = rotation_axis(1, rotation[1].to_radians());
+    let z = rotation_axis(2, rotation[2].to_radians());
+    let mut m = product(&z, &product(&y, &x));
+
+    for axis in 0..3 {
+        let scale = if scale[axis].abs() < f64::EPSILON {
+            0.001
+        } else {
+            scale[axis]
+        };
+        for value in &mut m[axis][..3] {
+            *value *= scale;
+        }
+    }
+    m[3] = [translation[0], translation[1], translation[2], 1.0];
+
+    *matrix = m;
+}
+
+/// Round `matrix` to single precision, e.g. to draw a model with
+/// [`Gizmo::draw_cube`](../struct.Gizmo.html#method.draw_cube). Far away from the origin this
+/// loses the precision [`manipulate`] keeps.
+pub fn to_f32(matrix: &Matrix4d) -> Matrix4 {
+    matrix.map(|column| column.map(|value| value as f32))
+}
+
+/// Widen a single-precision `matrix`, e.g. a delta matrix of
+/// [`Gizmo::manipulate`](../struct.Gizmo.html#method.manipulate), without rounding.
+pub fn to_f64(matrix: &Matrix4) -> Matrix4d {
+    matrix.map(|column| column.map(f64::from))
+}
+
+/// The camera position in world space, i.e. the translation of the inverse view matrix.
+fn camera_position(view: &Matrix4d) -> Vector3d {
+    match invert(view) {
+        Some(camera) => [camera[3][0], camera[3][1], camera[3][2]],
+        None => [0.0; 3],
+    }
+}
+
+fn negate(v: Vector3d) -> Vector3d {
+    [-v[0], -v[1], -v[2]]
+}
+
+fn length(v: &Vector4d) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// About 10,000 km from the origin, where `f32` steps are a metre apart and `f64` steps
+    /// about 2 nm.
+    const FAR: Vector3d = [1.0e7 + 0.123_456, -2.0e7 + 0.654_321, 5.0e6 + 0.5];
+
+    fn assert_close(a: &Matrix4d, b: &Matrix4d, tolerance: f64) {
+        for (a, b) in a.as_flattened().iter().zip(b.as_flattened()) {
+            assert!((a - b).abs() <= tolerance, "{a:?} != {b:?}");
+        }
+    }
+
+    /// A model at `FAR`, rotated around Y, and a view from 10 m away along Z.
+    fn far_away() -> (Matrix4d, Matrix4d) {
+        let model = product(&translation(&FAR), &rotation_axis(1, 0.5));
+        let eye = [FAR[0], FAR[1], FAR[2] + 10.0];
+        (translation(&negate(eye)), model)
+    }
+
+    #[test]
+    fn rebasing_moves_the_camera_to_the_origin() {
+        let (view, model) = far_away();
+        let rebased = Rebased::new(&view, &model);
+        assert_close(&rebased.view, &translation(&[0.0; 3]), 1e-9);
+        assert_eq!(to_f32(&rebased.model)[3], [0.0, 0.0, -10.0, 1.0]);
+    }
+
+    #[test]
+    fn unchanged_models_round_trip() {
+        let (view, mut model) = far_away();
+        let rebased = Rebased::new(&view, &model);
+        let before = to_f32(&rebased.model);
+        let delta = rebased.apply(&before, &before, &mut model).unwrap();
+        assert_close(&model, &far_away().1, 1e-8);
+        assert_close(&delta, &translation(&[0.0; 3]), 1e-8);
+    }
+
+    #[test]
+    fn deltas_are_applied_in_double_precision() {
+        let (view, mut model) = far_away();
+        let rebased = Rebased::new(&view, &model);
+        let before = to_f32(&rebased.model);
+        let mut after = before;
+        after[3][0] += 0.001;
+        let delta = rebased.apply(&before, &after, &mut model).unwrap();
+
+        let expected = product(&translation(&[0.001, 0.0, 0.0]), &far_away().1);
+        // The translation of `before` is 10 m, where `f32` resolves about a micrometre.
+        assert_close(&model, &expected, 1e-6);
+        assert_close(&delta, &translation(&[0.001, 0.0, 0.0]), 1e-6);
+        // Rounding the model itself to `f32` would be off by up to half a metre.
+        let rounded = to_f64(&to_f32(&expected));
+        assert!((rounded[3][0] - expected[3][0]).abs() > 1e-3);
+    }
+}
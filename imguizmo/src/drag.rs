@@ -0,0 +1,213 @@
+//! Drag lifecycle events. See [`DragTracker`].
+use crate::{math, Gizmo, Matrix4};
+
+use imgui::Key;
+use std::collections::HashMap;
+
+/// The id of gizmos drawn without [`Builder::with_id`](crate::Builder::with_id), matching
+/// ImGuizmo's default.
+pub const DEFAULT_ID: i32 = -1;
+
+/// A change in the drag state of a gizmo.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DragEvent {
+    /// The gizmo was grabbed. `initial` is the model matrix before the drag.
+    DragStarted { initial: Matrix4 },
+    /// The model matrix was changed. `delta` is the change since the previous frame, `total`
+    /// the change since the start of the drag, both applied on the left of the model matrix.
+    Dragging { delta: Matrix4, total: Matrix4 },
+    /// The gizmo was released. `result` is the model matrix after the drag.
+    DragEnded { initial: Matrix4, result: Matrix4 },
+    /// The drag was cancelled and the model matrix was restored to `initial`.
+    DragCancelled { initial: Matrix4 },
+}
+
+/// Tracks the drag state of gizmos across frames and turns it into [`DragEvent`]s.
+///
+/// Keep one tracker alive for as long as the gizmos and pass it to
+/// [`Builder::with_drag_tracker`](crate::Builder::with_drag_tracker). Gizmos are told apart
+/// by the id set with [`Builder::with_id`](crate::Builder::with_id).
+///
+/// ```rust,no_run
+/// # use imguizmo::{DragEvent, DragTracker, Gizmo, Matrix4};
+/// # let view = Matrix4::default();
+/// # let mut model = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let mut tracker = DragTracker::new();
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// gizmo
+///     .builder(&view, &mut model)
+///     .with_id(7)
+///     .with_drag_tracker(&mut tracker)
+///     .manipulate();
+///
+/// for event in tracker.events_for(&gizmo, 7) {
+///     if let DragEvent::DragEnded { initial, result } = event {
+///         // Record an undo step from `initial` to `result`.
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DragTracker {
+    cancel_key: Option<Key>,
+    drags: HashMap<i32, Drag>,
+    events: Vec<(i32, DragEvent)>,
+    frame: i32,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Drag {
+    initial: Matrix4,
+    cancelled: bool,
+}
+
+impl Default for DragTracker {
+    fn default() -> Self {
+        DragTracker {
+            cancel_key: Some(Key::Escape),
+            drags: HashMap::new(),
+            events: Vec::new(),
+            frame: i32::MIN,
+        }
+    }
+}
+
+impl DragTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the key that cancels a drag and restores the initial matrix. Defaults to `Escape`;
+    /// `None` disables cancelling.
+    pub fn with_cancel_key<T: Into<Option<Key>>>(mut self, cancel_key: T) -> Self {
+        self.cancel_key = cancel_key.into();
+        self
+    }
+
+    pub fn cancel_key(&self) -> Option<Key> {
+        self.cancel_key
+    }
+
+    /// The events of the current frame of `gizmo`, with the id of the gizmo they belong to.
+    /// Empty if no gizmo was drawn with this tracker this frame.
+    pub fn events(&self, gizmo: &Gizmo) -> &[(i32, DragEvent)] {
+        if gizmo.ui().frame_count() == self.frame {
+            &self.events
+        } else {
+            &[]
+        }
+    }
+
+    /// The events of the gizmo with the given `id` in the current frame of `gizmo`.
+    pub fn events_for(&self, gizmo: &Gizmo, id: i32) -> impl Iterator<Item = &DragEvent> {
+        self.events(gizmo)
+            .iter()
+            .filter(move |(event_id, _)| *event_id == id)
+            .map(|(_, event)| event)
+    }
+
+    /// Returns true if the gizmo with the given `id` is being dragged.
+    pub fn is_dragging(&self, id: i32) -> bool {
+        self.drags.get(&id).is_some_and(|drag| !drag.cancelled)
+    }
+
+    /// The model matrix at the start of the current drag of the gizmo with the given `id`.
+    pub fn initial(&self, id: i32) -> Option<&Matrix4> {
+        self.drags
+            .get(&id)
+            .filter(|drag| !drag.cancelled)
+            .map(|drag| &drag.initial)
+    }
+
+    /// Update the drag state of gizmo `id` after it was manipulated from `before` to `model`.
+    /// Returns true if the drag was cancelled and `model` restored.
+    pub(crate) fn update(
+        &mut self,
+        gizmo: &Gizmo,
+        id: i32,
+        before: &Matrix4,
+        model: &mut Matrix4,
+        changed: bool,
+    ) -> bool {
+        let frame = gizmo.ui().frame_count();
+        if frame != self.frame {
+            self.frame = frame;
+            self.events.clear();
+        }
+
+        let using = gizmo.is_using();
+        match self.drags.get(&id).copied() {
+            // A cancelled drag lasts until the mouse button is released, but it is no longer
+            // reported.
+            Some(Drag { cancelled: true, .. }) => {
+                if !using {
+                    self.drags.remove(&id);
+                }
+                false
+            }
+            Some(drag) if !using => {
+                self.drags.remove(&id);
+                self.events.push((
+                    id,
+                    DragEvent::DragEnded {
+                        initial: drag.initial,
+                        result: *model,
+                    },
+                ));
+                false
+            }
+            Some(drag) => {
+                let cancel = self.cancel_key.is_some_and(|key| gizmo.ui().is_key_pressed(key));
+                if cancel {
+                    *model = drag.initial;
+                    // Disabling the gizmo ends ImGuizmo's drag.
+                    gizmo.enable(false);
+                    gizmo.enable(true);
+                    self.drags.insert(
+                        id,
+                        Drag {
+                            cancelled: true,
+                            ..drag
+                        },
+                    );
+                    self.events.push((id, DragEvent::DragCancelled { initial: drag.initial }));
+                    return true;
+                }
+                if changed {
+                    self.push_dragging(id, &drag.initial, before, model);
+                }
+                false
+            }
+            None if using => {
+                self.drags.insert(
+                    id,
+                    Drag {
+                        initial: *before,
+                        cancelled: false,
+                    },
+                );
+                self.events.push((id, DragEvent::DragStarted { initial: *before }));
+                if changed {
+                    self.push_dragging(id, before, before, model);
+                }
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn push_dragging(&mut self, id: i32, initial: &Matrix4, before: &Matrix4, model: &Matrix4) {
+        let difference = |from: &Matrix4| match math::inverse::<Matrix4>(from) {
+            Some(inverse) => math::multiply(model, &inverse),
+            None => math::IDENTITY,
+        };
+        self.events.push((
+            id,
+            DragEvent::Dragging {
+                delta: difference(before),
+                total: difference(initial),
+            },
+        ));
+    }
+}
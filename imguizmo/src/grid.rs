@@ -0,0 +1,343 @@
+//! A configurable grid that follows the camera. See [`Grid`].
+use crate::{math, AsMatrix4, Matrix4, Vector3, Viewport};
+
+use imgui::ImColor32;
+
+/// The number of segments each grid line is split into to fade it with distance.
+const FADE_SEGMENTS: usize = 8;
+
+/// The plane a [`Grid`] lies in.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum GridPlane {
+    XY,
+    #[default]
+    XZ,
+    YZ,
+    /// The local XZ plane of a matrix, like `draw_grid`. The grid lines are one unit apart in
+    /// the matrix's space.
+    Matrix(Matrix4),
+}
+
+impl GridPlane {
+    /// The matrix whose local XZ plane is the grid plane.
+    pub fn matrix(&self) -> Matrix4 {
+        match *self {
+            GridPlane::XY => [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            GridPlane::XZ => math::IDENTITY,
+            GridPlane::YZ => [
+                [0.0, 1.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            GridPlane::Matrix(matrix) => matrix,
+        }
+    }
+
+    /// The colors of the world axes the two axes of the plane follow.
+    fn axis_colors(&self) -> [ImColor32; 2] {
+        const RED: ImColor32 = ImColor32::from_rgb(230, 64, 64);
+        const GREEN: ImColor32 = ImColor32::from_rgb(64, 200, 64);
+        const BLUE: ImColor32 = ImColor32::from_rgb(64, 96, 230);
+        match self {
+            GridPlane::XY => [RED, GREEN],
+            GridPlane::XZ | GridPlane::Matrix(_) => [RED, BLUE],
+            GridPlane::YZ => [GREEN, BLUE],
+        }
+    }
+}
+
+/// A grid drawn around the point below the camera, fading out with distance. With `adaptive`
+/// spacing, the lines get further apart by a factor of `subdivisions` as the camera moves away
+/// from the plane. The spacing in use can be handed to [`SnapConfig`](crate::SnapConfig)
+/// through `snap_matrix`.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4, Projection, Rect, SnapConfig};
+/// # use imguizmo::grid::{Grid, GridPlane};
+/// # let view = Matrix4::default();
+/// # let mut model = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let grid = Grid::new().with_plane(GridPlane::XZ).with_subdivisions(10);
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// let viewport = gizmo.viewport(Rect::from_display(ui), &view, Projection::Perspective { fovy: 45.0 });
+/// grid.draw(&viewport);
+/// viewport
+///     .builder(&mut model)
+///     .with_snap_config(SnapConfig::new().with_grid(&grid.snap_matrix(&view)))
+///     .manipulate();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Grid {
+    /// Defaults to `XZ`.
+    pub plane: GridPlane,
+    /// The distance between minor lines closest to the plane. Defaults to `1.0`.
+    pub spacing: f32,
+    /// The number of minor cells per major cell. Defaults to `10`.
+    pub subdivisions: u32,
+    /// Space the lines further apart as the camera moves away. Defaults to `true`.
+    pub adaptive: bool,
+    /// The distance in minor cells from the point below the camera at which the lines have
+    /// faded out. Defaults to `50.0`.
+    pub fade_distance: f32,
+    pub minor_color: ImColor32,
+    pub major_color: ImColor32,
+    /// The colors of the lines along the first and second axis of the plane, e.g. X and Z.
+    /// Defaults to `None`, which colors them like the world axes they follow.
+    pub axis_colors: Option<[ImColor32; 2]>,
+    /// The line thickness in pixels. Defaults to `1.0`.
+    pub thickness: f32,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid {
+            plane: GridPlane::XZ,
+            spacing: 1.0,
+            subdivisions: 10,
+            adaptive: true,
+            fade_distance: 50.0,
+            minor_color: ImColor32::from_rgba(128, 128, 128, 96),
+            major_color: ImColor32::from_rgba(160, 160, 160, 192),
+            axis_colors: None,
+            thickness: 1.0,
+        }
+    }
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_plane(mut self, plane: GridPlane) -> Self {
+        self.plane = plane;
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn with_subdivisions(mut self, subdivisions: u32) -> Self {
+        self.subdivisions = subdivisions;
+        self
+    }
+
+    pub fn with_adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    pub fn with_fade_distance(mut self, fade_distance: f32) -> Self {
+        self.fade_distance = fade_distance;
+        self
+    }
+
+    pub fn with_colors(mut self, minor: impl Into<ImColor32>, major: impl Into<ImColor32>) -> Self {
+        self.minor_color = minor.into();
+        self.major_color = major.into();
+        self
+    }
+
+    pub fn with_axis_colors<T: Into<Option<[ImColor32; 2]>>>(mut self, axis_colors: T) -> Self {
+        self.axis_colors = axis_colors.into();
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// The distance between minor lines seen from a camera with the given `view`, in the
+    /// plane's units.
+    pub fn spacing(&self, view: &impl AsMatrix4) -> f32 {
+        match self.local_eye(view) {
+            Some(eye) => self.level(eye[1].abs()).0,
+            None => self.spacing,
+        }
+    }
+
+    /// The plane matrix scaled to the minor lines seen from a camera with the given `view`,
+    /// for [`SnapConfig::with_grid`](crate::SnapConfig::with_grid).
+    pub fn snap_matrix(&self, view: &impl AsMatrix4) -> Matrix4 {
+        let spacing = self.spacing(view);
+        let mut matrix = self.plane.matrix();
+        for column in matrix.iter_mut().take(3) {
+            *column = column.map(|value| value * spacing);
+        }
+        matrix
+    }
+
+    /// Draw the grid in `viewport`.
+    pub fn draw(&self, viewport: &Viewport) {
+        let eye = match self.local_eye(viewport.view()) {
+            Some(eye) => eye,
+            None => return,
+        };
+        let (spacing, fraction) = self.level(eye[1].abs());
+        let radius = self.fade_distance * spacing;
+        let subdivisions = self.subdivisions.max(2) as i64;
+        let axis_colors = self.axis_colors.unwrap_or_else(|| self.plane.axis_colors());
+        // Fade the minor lines out as the next level comes closer.
+        let minor_color = faded(self.minor_color, 1.0 - fraction);
+        let plane = self.plane.matrix();
+
+        // Lines along the second axis, at steps along the first axis, then the other way.
+        for (across, along) in [(0, 2), (2, 0)] {
+            let center = [eye[across], eye[along]];
+            let first = ((center[0] - radius) / spacing).ceil() as i64;
+            let last = ((center[0] + radius) / spacing).floor() as i64;
+            for line in first..=last {
+                let color = if line == 0 {
+                    axis_colors[if along == 0 { 0 } else { 1 }]
+                } else if line % subdivisions == 0 {
+                    self.major_color
+                } else {
+                    minor_color
+                };
+                let offset = line as f32 * spacing;
+                let half = (radius * radius - (offset - center[0]).powi(2)).max(0.0).sqrt();
+                let point = |t: f32| {
+                    let mut local = [0.0; 3];
+                    local[across] = offset;
+                    local[along] = center[1] - half + 2.0 * half * t;
+                    local
+                };
+                for segment in 0..FADE_SEGMENTS {
+                    let (t0, t1) = (
+                        segment as f32 / FADE_SEGMENTS as f32,
+                        (segment + 1) as f32 / FADE_SEGMENTS as f32,
+                    );
+                    let (a, b) = (point(t0), point(t1));
+                    let middle = point((t0 + t1) * 0.5);
+                    let distance = (middle[across] - center[0]).hypot(middle[along] - center[1]);
+                    let fade = (1.0 - distance / radius).clamp(0.0, 1.0);
+                    viewport.draw_line_3d(
+                        &math::transform_point::<Vector3>(&plane, &a),
+                        &math::transform_point::<Vector3>(&plane, &b),
+                        faded(color, fade * fade),
+                        self.thickness,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The camera position in the space of the plane, where the plane is `y = 0`.
+    fn local_eye(&self, view: &impl AsMatrix4) -> Option<Vector3> {
+        let camera: Matrix4 = math::affine_inverse(view)?;
+        let inverse: Matrix4 = math::inverse(&self.plane.matrix())?;
+        Some(math::transform_point(&inverse, &math::column3(&camera[3])))
+    }
+
+    /// The spacing of the minor lines at `height` above the plane, and how far the camera is
+    /// on its way to the next spacing, from `0` to `1`.
+    fn level(&self, height: f32) -> (f32, f32) {
+        if !self.adaptive {
+            return (self.spacing, 0.0);
+        }
+        let subdivisions = self.subdivisions.max(2) as f32;
+        let level = (height / (self.spacing * subdivisions)).log(subdivisions).max(0.0);
+        (self.spacing * subdivisions.powf(level.floor()), level.fract())
+    }
+}
+
+/// `color` with its alpha scaled by `factor`.
+fn faded(color: ImColor32, factor: f32) -> ImColor32 {
+    let [r, g, b, a]: [f32; 4] = color.into();
+    ImColor32::from([r, g, b, a * factor])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A view from a camera at `eye`.
+    fn view(eye: Vector3) -> Matrix4 {
+        math::translation(&eye.map(|value| -value))
+    }
+
+    #[test]
+    fn levels_at_powers_of_subdivisions() {
+        for (spacing, subdivisions) in [(1.0, 10), (0.5, 10), (1.0, 2), (0.25, 4), (2.0, 5)] {
+            let grid = Grid::new().with_spacing(spacing).with_subdivisions(subdivisions);
+            let base = subdivisions as f32;
+            // Below the first power, the spacing is not adapted.
+            assert_eq!(grid.level(0.0), (spacing, 0.0));
+            assert_eq!(grid.level(spacing), (spacing, 0.0));
+            for power in 0..6 {
+                let level = spacing * base.powi(power);
+                let (minor, fraction) = grid.level(level * base);
+                assert_eq!((minor, fraction), (level, 0.0), "{spacing} {subdivisions} {power}");
+                // Halfway to the next level on a logarithmic scale.
+                let (minor, fraction) = grid.level(level * base * base.sqrt());
+                assert_eq!(minor, level);
+                assert!((fraction - 0.5).abs() < 1e-4, "{fraction}");
+            }
+        }
+    }
+
+    #[test]
+    fn subdivisions_are_at_least_two() {
+        let grid = Grid::new().with_subdivisions(1);
+        assert_eq!(grid.level(8.0), (4.0, 0.0));
+        let grid = Grid::new().with_subdivisions(0);
+        assert_eq!(grid.level(8.0), Grid::new().with_subdivisions(2).level(8.0));
+    }
+
+    #[test]
+    fn fixed_spacing_ignores_height() {
+        let grid = Grid::new().with_spacing(0.5).with_adaptive(false);
+        for height in [0.0, 5.0, 1000.0, 1e9] {
+            assert_eq!(grid.level(height), (0.5, 0.0));
+        }
+        assert_eq!(grid.spacing(&view([0.0, 1000.0, 0.0])), 0.5);
+    }
+
+    #[test]
+    fn spacing_follows_the_height_above_the_plane() {
+        let eye = [3.0, -250.0, 7.0];
+        let grid = Grid::new();
+        assert_eq!(grid.spacing(&view(eye)), 10.0);
+        // The camera is in the XY plane of `GridPlane::XY`.
+        assert_eq!(grid.with_plane(GridPlane::XY).spacing(&view(eye)), 1.0);
+        assert_eq!(grid.with_plane(GridPlane::YZ).spacing(&view([250.0, 0.0, 0.0])), 10.0);
+        // A singular view keeps the configured spacing.
+        assert_eq!(grid.spacing(&[[0.0; 4]; 4]), 1.0);
+    }
+
+    #[test]
+    fn snap_matrix_scales_the_plane_axes() {
+        let mut scaled = math::translation(&[1.0, 2.0, 3.0]);
+        for axis in scaled.iter_mut().take(3).flatten() {
+            *axis *= 2.0;
+        }
+        for (plane, eye) in [
+            (GridPlane::XY, [0.0, 0.0, 250.0]),
+            (GridPlane::XZ, [0.0, 250.0, 0.0]),
+            (GridPlane::YZ, [250.0, 0.0, 0.0]),
+            // 250 units above the plane, but 125 in its own space.
+            (GridPlane::Matrix(scaled), [1.0, 252.0, 3.0]),
+        ] {
+            let matrix = Grid::new().with_plane(plane).snap_matrix(&view(eye));
+            let expected = plane.matrix();
+            for axis in 0..3 {
+                let column = math::column3(&matrix[axis]);
+                let length = math::length(&math::column3(&expected[axis])) * 10.0;
+                assert!((math::length(&column) - length).abs() < 1e-4, "{plane:?} {axis}");
+                let direction = math::normalize(&math::column3(&expected[axis]));
+                assert!((math::dot(&math::normalize(&column), &direction) - 1.0).abs() < 1e-6);
+            }
+            assert_eq!(matrix[3], expected[3]);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Undo and redo for gizmo edits.
+//!
+//! A [`History`] records one [`Transaction`] per completed drag, as the matrices before and
+//! after the drag of a target identified by a key of your choice. Feed it the events of a
+//! [`DragTracker`] with [`History::record_drags`], or record transactions yourself.
+//!
+//! ```rust
+//! use imguizmo::history::History;
+//! # use imguizmo::Matrix4;
+//! # let before = Matrix4::default();
+//! # let after = [[1.0; 4]; 4];
+//!
+//! let mut history = History::new();
+//! history.record("cube", before, after);
+//!
+//! let undone = history.undo().unwrap();
+//! assert_eq!((undone.target, undone.before), ("cube", before));
+//! let redone = history.redo().unwrap();
+//! assert_eq!(redone.after, after);
+//! ```
+use crate::{DragEvent, DragTracker, Gizmo, Matrix4};
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A change of the matrix of `target` from `before` to `after`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transaction<K> {
+    pub target: K,
+    pub before: Matrix4,
+    pub after: Matrix4,
+}
+
+/// When a new transaction is merged into the previous one instead of being recorded on its
+/// own. A merged transaction keeps the `before` of the previous one and takes the `after` of
+/// the new one. Only a transaction that starts where the previous one ended is merged, and
+/// never into a transaction that was just undone or redone.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MergePolicy {
+    /// Record every transaction.
+    #[default]
+    Never,
+    /// Merge consecutive transactions of the same target.
+    SameTarget,
+    /// Merge consecutive transactions of the same target recorded less than the given
+    /// duration apart, e.g. a series of quick nudges.
+    SameTargetWithin(Duration),
+}
+
+/// Undo and redo stacks of [`Transaction`]s.
+#[derive(Clone, Debug)]
+pub struct History<K> {
+    undo: VecDeque<Transaction<K>>,
+    redo: Vec<Transaction<K>>,
+    capacity: Option<usize>,
+    merge_policy: MergePolicy,
+    last_recorded: Option<Instant>,
+    /// Set by `undo`, `redo` and `clear` so that the next transaction is not merged.
+    merge_barrier: bool,
+}
+
+impl<K> Default for History<K> {
+    fn default() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity: Some(100),
+            merge_policy: MergePolicy::Never,
+            last_recorded: None,
+            merge_barrier: false,
+        }
+    }
+}
+
+impl<K: Clone + PartialEq> History<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of transactions that can be undone. The oldest transactions
+    /// are dropped first. Defaults to `100`; `None` keeps all transactions.
+    pub fn with_capacity<T: Into<Option<usize>>>(mut self, capacity: T) -> Self {
+        self.capacity = capacity.into();
+        self.truncate();
+        self
+    }
+
+    /// Set when transactions are merged. Defaults to `MergePolicy::Never`.
+    pub fn with_merge_policy(mut self, merge_policy: MergePolicy) -> Self {
+        self.merge_policy = merge_policy;
+        self
+    }
+
+    /// Record a change of `target` and clear the redo stack. Changes that leave the matrix as
+    /// it was are ignored.
+    pub fn record(&mut self, target: K, before: Matrix4, after: Matrix4) {
+        if before == after {
+            return;
+        }
+        let now = Instant::now();
+        let merge = !self.merge_barrier
+            && match self.merge_policy {
+                MergePolicy::Never => false,
+                MergePolicy::SameTarget => true,
+                MergePolicy::SameTargetWithin(duration) => self
+                    .last_recorded
+                    .is_some_and(|last| now.duration_since(last) < duration),
+            };
+        self.last_recorded = Some(now);
+        self.merge_barrier = false;
+        self.redo.clear();
+
+        match self.undo.back_mut() {
+            Some(last) if merge && last.target == target && last.after == before => {
+                last.after = after
+            }
+            _ => {
+                self.undo.push_back(Transaction {
+                    target,
+                    before,
+                    after,
+                });
+                self.truncate();
+            }
+        }
+    }
+
+    /// Record the drags of gizmo `id` that ended this frame of `gizmo` as changes of
+    /// `target`. Cancelled drags are not recorded.
+    pub fn record_drags(&mut self, gizmo: &Gizmo, tracker: &DragTracker, id: i32, target: K) {
+        for event in tracker.events_for(gizmo, id) {
+            if let DragEvent::DragEnded { initial, result } = event {
+                self.record(target.clone(), *initial, *result);
+            }
+        }
+    }
+
+    /// Move the last transaction to the redo stack and return it. Apply its `before` matrix
+    /// to its `target`.
+    pub fn undo(&mut self) -> Option<&Transaction<K>> {
+        let transaction = self.undo.pop_back()?;
+        self.redo.push(transaction);
+        self.last_recorded = None;
+        self.merge_barrier = true;
+        self.redo.last()
+    }
+
+    /// Move the last undone transaction back to the undo stack and return it. Apply its
+    /// `after` matrix to its `target`.
+    pub fn redo(&mut self) -> Option<&Transaction<K>> {
+        let transaction = self.redo.pop()?;
+        self.undo.push_back(transaction);
+        self.last_recorded = None;
+        self.merge_barrier = true;
+        self.undo.back()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The transactions that can be undone, oldest first.
+    pub fn undo_stack(&self) -> impl Iterator<Item = &Transaction<K>> {
+        self.undo.iter()
+    }
+
+    /// The transactions that can be redone, the next one to be redone last.
+    pub fn redo_stack(&self) -> impl Iterator<Item = &Transaction<K>> {
+        self.redo.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_recorded = None;
+        self.merge_barrier = true;
+    }
+
+    fn truncate(&mut self) {
+        if let Some(capacity) = self.capacity {
+            while self.undo.len() > capacity {
+                self.undo.pop_front();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(x: f32) -> Matrix4 {
+        crate::math::translation(&[x, 0.0, 0.0])
+    }
+
+    fn history(merge_policy: MergePolicy) -> History<&'static str> {
+        History::new().with_merge_policy(merge_policy)
+    }
+
+    fn undo_stack(history: &History<&'static str>) -> Vec<(&'static str, Matrix4, Matrix4)> {
+        let stack = history.undo_stack();
+        stack.map(|t| (t.target, t.before, t.after)).collect()
+    }
+
+    #[test]
+    fn capacity_drops_the_oldest_transactions() {
+        let mut history = history(MergePolicy::Never).with_capacity(2);
+        for x in 0..3 {
+            history.record("cube", matrix(x as f32), matrix(x as f32 + 1.0));
+        }
+        let stack = undo_stack(&history);
+        assert_eq!(stack, [("cube", matrix(1.0), matrix(2.0)), ("cube", matrix(2.0), matrix(3.0))]);
+
+        let history = history.with_capacity(1);
+        assert_eq!(undo_stack(&history), [("cube", matrix(2.0), matrix(3.0))]);
+    }
+
+    #[test]
+    fn recording_clears_the_redo_stack() {
+        let mut history = history(MergePolicy::Never);
+        history.record("cube", matrix(0.0), matrix(1.0));
+        history.record("cube", matrix(1.0), matrix(2.0));
+        assert_eq!(history.undo().map(|t| t.before), Some(matrix(1.0)));
+        assert!(history.can_redo());
+
+        history.record("cube", matrix(1.0), matrix(5.0));
+        assert!(!history.can_redo());
+        assert!(history.redo().is_none());
+        // Changes that leave the matrix as it was are not recorded.
+        history.record("cube", matrix(5.0), matrix(5.0));
+        assert_eq!(history.undo_stack().count(), 2);
+    }
+
+    #[test]
+    fn never_keeps_every_transaction() {
+        let mut history = history(MergePolicy::Never);
+        history.record("cube", matrix(0.0), matrix(1.0));
+        history.record("cube", matrix(1.0), matrix(2.0));
+        assert_eq!(history.undo_stack().count(), 2);
+    }
+
+    #[test]
+    fn same_target_merges_continuous_edits() {
+        let mut history = history(MergePolicy::SameTarget);
+        history.record("cube", matrix(0.0), matrix(1.0));
+        history.record("cube", matrix(1.0), matrix(2.0));
+        assert_eq!(undo_stack(&history), [("cube", matrix(0.0), matrix(2.0))]);
+
+        // Another target, or an edit that does not start where the last one ended.
+        history.record("sphere", matrix(2.0), matrix(3.0));
+        history.record("sphere", matrix(0.0), matrix(1.0));
+        assert_eq!(
+            undo_stack(&history),
+            [
+                ("cube", matrix(0.0), matrix(2.0)),
+                ("sphere", matrix(2.0), matrix(3.0)),
+                ("sphere", matrix(0.0), matrix(1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_target_within_merges_quick_edits() {
+        let mut history = history(MergePolicy::SameTargetWithin(Duration::from_secs(3600)));
+        history.record("cube", matrix(0.0), matrix(1.0));
+        history.record("cube", matrix(1.0), matrix(2.0));
+        assert_eq!(undo_stack(&history), [("cube", matrix(0.0), matrix(2.0))]);
+
+        let mut history = history.with_merge_policy(MergePolicy::SameTargetWithin(Duration::ZERO));
+        history.record("cube", matrix(2.0), matrix(3.0));
+        assert_eq!(history.undo_stack().count(), 2);
+    }
+
+    #[test]
+    fn undo_and_redo_stop_merging() {
+        let mut history = history(MergePolicy::SameTarget);
+        history.record("cube", matrix(0.0), matrix(1.0));
+        history.record("sphere", matrix(0.0), matrix(1.0));
+        history.undo();
+        // Continues the cube edit, but must not merge into a transaction before an undo.
+        history.record("cube", matrix(1.0), matrix(2.0));
+        assert_eq!(
+            undo_stack(&history),
+            [("cube", matrix(0.0), matrix(1.0)), ("cube", matrix(1.0), matrix(2.0))]
+        );
+
+        history.undo();
+        history.redo();
+        history.record("cube", matrix(2.0), matrix(3.0));
+        assert_eq!(history.undo_stack().count(), 3);
+        // Merging resumes after the first transaction.
+        history.record("cube", matrix(3.0), matrix(4.0));
+        assert_eq!(history.undo_stack().count(), 3);
+        assert_eq!(history.undo_stack().last().map(|t| t.after), Some(matrix(4.0)));
+
+        history.clear();
+        history.record("cube", matrix(5.0), matrix(6.0));
+        history.record("cube", matrix(6.0), matrix(7.0));
+        assert_eq!(undo_stack(&history), [("cube", matrix(5.0), matrix(7.0))]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Editor-style keyboard shortcuts for the gizmo operation, mode and snapping.
+use crate::{Axis, Mode, Operation};
+
+use imgui::{Key, Ui, WindowFocusedFlags};
+
+/// The editable state of a gizmo: what it does and how. Pass it to
+/// [`Builder::with_config`](crate::Builder::with_config) and edit it with [`GizmoHotkeys`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GizmoConfig {
+    pub operation: Operation,
+    pub mode: Mode,
+    /// Constrain translations, rotations and scales to a single axis.
+    pub axis: Option<Axis>,
+    /// Snapping is enabled. Passed to [`Builder::with_config`](crate::Builder::with_config),
+    /// this replaces the `enabled` flag of a [`SnapConfig`](crate::SnapConfig).
+    pub snap: bool,
+    /// The snap modifier is held, which inverts `snap` temporarily.
+    pub snap_inverted: bool,
+}
+
+impl Default for GizmoConfig {
+    fn default() -> Self {
+        GizmoConfig {
+            operation: Operation::Rotate,
+            mode: Mode::Local,
+            axis: None,
+            snap: false,
+            snap_inverted: false,
+        }
+    }
+}
+
+impl GizmoConfig {
+    /// Returns true if snapping is enabled, taking the snap modifier into account.
+    pub fn is_snapping(&self) -> bool {
+        self.snap != self.snap_inverted
+    }
+}
+
+/// The keys used by [`GizmoHotkeys`]. `None` disables a shortcut.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyMap {
+    pub translate: Option<Key>,
+    pub rotate: Option<Key>,
+    pub scale: Option<Key>,
+    pub bounds: Option<Key>,
+    /// Constrain to the X axis, or remove the constraint if it is already constrained to X.
+    pub axis_x: Option<Key>,
+    pub axis_y: Option<Key>,
+    pub axis_z: Option<Key>,
+    /// Toggle between `Local` and `World` mode.
+    pub toggle_mode: Option<Key>,
+    /// Toggle snapping.
+    pub toggle_snap: Option<Key>,
+    /// Invert snapping while held.
+    pub snap_modifier: Option<Key>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap {
+            translate: Some(Key::W),
+            rotate: Some(Key::E),
+            scale: Some(Key::R),
+            bounds: None,
+            axis_x: Some(Key::X),
+            axis_y: Some(Key::Y),
+            axis_z: Some(Key::Z),
+            toggle_mode: Some(Key::Space),
+            toggle_snap: None,
+            snap_modifier: Some(Key::ModCtrl),
+        }
+    }
+}
+
+/// Reads the keyboard and edits a [`GizmoConfig`]: W/E/R select the operation, X/Y/Z
+/// constrain it to an axis, Space toggles between local and world mode and Ctrl snaps while
+/// held. The keys can be changed with a [`KeyMap`].
+///
+/// Call `update` inside of the viewport window. Nothing happens unless that window is
+/// focused and no text is being edited, and shortcuts are ignored while Ctrl, Alt or Super
+/// is held.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4};
+/// # use imguizmo::hotkeys::{GizmoConfig, GizmoHotkeys};
+/// # let view = Matrix4::default();
+/// # let mut model = Matrix4::default();
+/// # let mut snap = [1.0; 3];
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let hotkeys = GizmoHotkeys::new();
+/// let mut config = GizmoConfig::default();
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// hotkeys.update(ui, &mut config);
+/// gizmo
+///     .builder(&view, &mut model)
+///     .with_config(&config)
+///     .with_snap(&mut snap)
+///     .manipulate();
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GizmoHotkeys {
+    pub key_map: KeyMap,
+}
+
+impl GizmoHotkeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_key_map(mut self, key_map: KeyMap) -> Self {
+        self.key_map = key_map;
+        self
+    }
+
+    /// Apply the keys pressed this frame to `config`. Returns true if `config` was changed.
+    pub fn update(&self, ui: &Ui, config: &mut GizmoConfig) -> bool {
+        let before = *config;
+        let active = ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+            && !ui.io().want_text_input;
+        // Leave shortcuts like Ctrl+Z to the application.
+        let io = ui.io();
+        let modified = io.key_ctrl || io.key_alt || io.key_super;
+        // Toggles would flip back and forth while a key is held with key repeat.
+        let pressed = |key: Option<Key>| {
+            active && !modified && key.is_some_and(|key| ui.is_key_pressed_no_repeat(key))
+        };
+        let keys = &self.key_map;
+
+        for (key, operation) in [
+            (keys.translate, Operation::Translate),
+            (keys.rotate, Operation::Rotate),
+            (keys.scale, Operation::Scale),
+            (keys.bounds, Operation::Bounds),
+        ] {
+            if pressed(key) {
+                config.operation = operation;
+            }
+        }
+
+        for (key, axis) in [
+            (keys.axis_x, Axis::X),
+            (keys.axis_y, Axis::Y),
+            (keys.axis_z, Axis::Z),
+        ] {
+            if pressed(key) {
+                config.axis = if config.axis == Some(axis) {
+                    None
+                } else {
+                    Some(axis)
+                };
+            }
+        }
+
+        if pressed(keys.toggle_mode) {
+            config.mode = match config.mode {
+                Mode::Local => Mode::World,
+                Mode::World | Mode::Parent => Mode::Local,
+            };
+        }
+        if pressed(keys.toggle_snap) {
+            config.snap = !config.snap;
+        }
+        config.snap_inverted =
+            active && keys.snap_modifier.is_some_and(|key| ui.is_key_down(key));
+
+        *config != before
+    }
+}
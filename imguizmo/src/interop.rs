@@ -0,0 +1,164 @@
+//! Conversions between [`Matrix4`]/[`Vector3`] and the types of common math libraries.
+//!
+//! Enable the `mint`, `glam`, `nalgebra` or `cgmath` features to pass their matrices and
+//! vectors straight to [`Builder`](crate::Builder), [`Gizmo::manipulate`](crate::Gizmo::manipulate),
+//! the decompose helpers and the projection helpers. All of these types share the column-major
+//! layout ImGuizmo expects, so references are reinterpreted in place instead of copied.
+//!
+//! Functions that return a new matrix or vector, such as [`perspective`](crate::perspective),
+//! [`recompose`](crate::decompose::recompose) and the [`math`](crate::math) helpers, return any
+//! [`FromMatrix4`] or [`FromVector3`] type. Name the type when it can't be inferred, e.g.
+//! `let projection: glam::Mat4 = imguizmo::perspective(45.0, aspect_ratio, 0.1, 100.0);`.
+use crate::{Matrix4, Vector3};
+
+/// A matrix type with the same memory layout as a column-major [`Matrix4`].
+///
+/// # Safety
+///
+/// Implementors must have the size of `[[f32; 4]; 4]`, an alignment of at least that of `f32`,
+/// and store their 16 components column by column.
+pub unsafe trait AsMatrix4: Sized {
+    fn as_matrix4(&self) -> &Matrix4 {
+        unsafe { &*(self as *const Self as *const Matrix4) }
+    }
+
+    fn as_matrix4_mut(&mut self) -> &mut Matrix4 {
+        unsafe { &mut *(self as *mut Self as *mut Matrix4) }
+    }
+}
+
+/// A vector type with the same memory layout as a [`Vector3`].
+///
+/// # Safety
+///
+/// Implementors must have the size of `[f32; 3]`, an alignment of at least that of `f32`,
+/// and store their components in `x`, `y`, `z` order.
+pub unsafe trait AsVector3: Sized {
+    fn as_vector3(&self) -> &Vector3 {
+        unsafe { &*(self as *const Self as *const Vector3) }
+    }
+
+    fn as_vector3_mut(&mut self) -> &mut Vector3 {
+        unsafe { &mut *(self as *mut Self as *mut Vector3) }
+    }
+}
+
+/// A matrix type that can be built from a column-major [`Matrix4`]. Implemented for every
+/// [`AsMatrix4`] type by copying the components.
+pub trait FromMatrix4 {
+    fn from_matrix4(matrix: Matrix4) -> Self;
+}
+
+/// A vector type that can be built from a [`Vector3`]. Implemented for every [`AsVector3`]
+/// type by copying the components.
+pub trait FromVector3 {
+    fn from_vector3(vector: Vector3) -> Self;
+}
+
+impl<M: AsMatrix4> FromMatrix4 for M {
+    fn from_matrix4(matrix: Matrix4) -> Self {
+        unsafe { std::mem::transmute_copy(&matrix) }
+    }
+}
+
+impl<V: AsVector3> FromVector3 for V {
+    fn from_vector3(vector: Vector3) -> Self {
+        unsafe { std::mem::transmute_copy(&vector) }
+    }
+}
+
+unsafe impl AsMatrix4 for Matrix4 {}
+
+unsafe impl AsVector3 for Vector3 {}
+
+/// Checks the layout requirements of `AsMatrix4` and `AsVector3` at compile time.
+#[allow(unused_macros)]
+macro_rules! assert_layout {
+    ($ty:ty, $target:ty) => {
+        const _: () = assert!(
+            std::mem::size_of::<$ty>() == std::mem::size_of::<$target>()
+                && std::mem::align_of::<$ty>() >= std::mem::align_of::<f32>()
+        );
+    };
+}
+
+#[cfg(feature = "mint")]
+mod mint_impls {
+    use super::{AsMatrix4, AsVector3};
+    use crate::{Matrix4, Vector3};
+
+    assert_layout!(mint::ColumnMatrix4<f32>, Matrix4);
+    assert_layout!(mint::Vector3<f32>, Vector3);
+
+    unsafe impl AsMatrix4 for mint::ColumnMatrix4<f32> {}
+
+    unsafe impl AsVector3 for mint::Vector3<f32> {}
+}
+
+#[cfg(feature = "glam")]
+mod glam_impls {
+    use super::{AsMatrix4, AsVector3};
+    use crate::{Matrix4, Vector3};
+
+    assert_layout!(glam::Mat4, Matrix4);
+    assert_layout!(glam::Vec3, Vector3);
+
+    unsafe impl AsMatrix4 for glam::Mat4 {}
+
+    unsafe impl AsVector3 for glam::Vec3 {}
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impls {
+    use super::{AsMatrix4, AsVector3};
+    use crate::{Matrix4, Vector3};
+
+    assert_layout!(nalgebra::Matrix4<f32>, Matrix4);
+    assert_layout!(nalgebra::Vector3<f32>, Vector3);
+
+    unsafe impl AsMatrix4 for nalgebra::Matrix4<f32> {}
+
+    unsafe impl AsVector3 for nalgebra::Vector3<f32> {}
+}
+
+#[cfg(feature = "cgmath")]
+mod cgmath_impls {
+    use super::{AsMatrix4, AsVector3};
+    use crate::{Matrix4, Vector3};
+
+    assert_layout!(cgmath::Matrix4<f32>, Matrix4);
+    assert_layout!(cgmath::Vector3<f32>, Vector3);
+
+    unsafe impl AsMatrix4 for cgmath::Matrix4<f32> {}
+
+    unsafe impl AsVector3 for cgmath::Vector3<f32> {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{decompose, math, perspective, Matrix4, Vector3};
+
+    #[test]
+    fn outputs_match_native_matrices() {
+        let native: Matrix4 = perspective(45.0, 1.5, 0.1, 100.0);
+        assert_eq!(native[2][3], -1.0);
+        #[cfg(feature = "glam")]
+        {
+            let glam: glam::Mat4 = perspective(45.0, 1.5, 0.1, 100.0);
+            assert_eq!(glam.to_cols_array_2d(), native);
+            let point: glam::Vec3 = math::transform_point(&glam, &glam::Vec3::new(1.0, 2.0, -3.0));
+            let expected: Vector3 = math::transform_point(&native, &[1.0, 2.0, -3.0]);
+            assert_eq!(point.to_array(), expected);
+        }
+        #[cfg(feature = "cgmath")]
+        {
+            let cgmath: cgmath::Matrix4<f32> = perspective(45.0, 1.5, 0.1, 100.0);
+            let cgmath: Matrix4 = cgmath.into();
+            assert_eq!(cgmath, native);
+        }
+        let rotation = decompose::Rotation::Quaternion([0.0, 0.0, 0.0, 1.0]);
+        let model: Matrix4 = decompose::recompose(&[1.0, 2.0, 3.0], &rotation, &[1.0; 3]);
+        let origin: Vector3 = math::transform_point(&model, &[0.0; 3]);
+        assert_eq!(origin, [1.0, 2.0, 3.0]);
+    }
+}
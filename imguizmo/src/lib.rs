@@ -12,13 +12,43 @@
 //!     .manipulate();
 //! ```
 //!
-use ffi::{ImGuizmo_ViewManipulate, ImVec2, ImGuizmo_OPERATION_TRANSLATE, ImGuizmo_OPERATION_ROTATE, ImGuizmo_OPERATION_SCALE, ImGuizmo_OPERATION_BOUNDS, ImGuizmo_MODE_LOCAL, ImGuizmo_MODE_WORLD};
+use ffi::{ImGuizmo_ViewManipulate, ImVec2, ImGuizmo_OPERATION_TRANSLATE, ImGuizmo_OPERATION_ROTATE, ImGuizmo_OPERATION_SCALE, ImGuizmo_OPERATION_BOUNDS, ImGuizmo_OPERATION_TRANSLATE_X, ImGuizmo_OPERATION_ROTATE_X, ImGuizmo_OPERATION_SCALE_X, ImGuizmo_MODE_LOCAL, ImGuizmo_MODE_WORLD};
 use imguizmo_sys as ffi;
 
 use imgui::Ui;
 
+use std::cell::Cell;
+use std::marker::PhantomData;
 use std::ptr;
 
+pub mod camera;
+pub mod debug_draw;
+pub mod decompose;
+pub mod double;
+mod drag;
+pub mod grid;
+pub mod history;
+pub mod hotkeys;
+pub mod interop;
+pub mod math;
+mod overlay;
+pub mod picking;
+mod selection;
+mod snap;
+mod transform;
+pub mod validation;
+mod viewport;
+pub mod widgets;
+
+pub use drag::{DragEvent, DragTracker};
+pub use interop::{AsMatrix4, AsVector3, FromMatrix4, FromVector3};
+pub use picking::{Ray, SurfaceHit, SurfaceSnap};
+pub use selection::{Pivot, SelectionState};
+pub use snap::SnapConfig;
+pub use transform::Transform;
+pub use validation::{Validation, ValidationError};
+pub use viewport::Viewport;
+
 pub type Vector2 = [f32; 2];
 pub type Vector3 = [f32; 3];
 pub type Vector4 = [f32; 4];
@@ -33,11 +63,49 @@ pub enum Operation {
     Bounds = ImGuizmo_OPERATION_BOUNDS,
 }
 
+impl Operation {
+    /// ImGuizmo's operation bits, limited to a single `axis` for translations, rotations and
+    /// scales.
+    pub(crate) fn bits(self, axis: Option<Axis>) -> i32 {
+        let first = match self {
+            Operation::Translate => ImGuizmo_OPERATION_TRANSLATE_X,
+            Operation::Rotate => ImGuizmo_OPERATION_ROTATE_X,
+            Operation::Scale => ImGuizmo_OPERATION_SCALE_X,
+            Operation::Bounds => return self as i32,
+        };
+        match axis {
+            Some(axis) => first << axis as i32,
+            None => self as i32,
+        }
+    }
+}
+
+/// An axis that translations, rotations and scales can be constrained to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X = 0,
+    Y = 1,
+    Z = 2,
+}
+
 #[repr(i32)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Mode {
     Local = ImGuizmo_MODE_LOCAL,
     World = ImGuizmo_MODE_WORLD,
+    /// Align the gizmo with the rotation of the parent set with
+    /// [`Builder::with_parent`](struct.Builder.html#method.with_parent). Same as `World`
+    /// without a parent.
+    Parent = ImGuizmo_MODE_WORLD + 1,
+}
+
+/// The clip space depth range a projection matrix maps the near and far planes to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DepthRange {
+    /// `-1..1`, like OpenGL and [`perspective`].
+    NegativeOneToOne,
+    /// `0..1`, like Direct3D, Vulkan and [`orthographic`].
+    ZeroToOne,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -58,11 +126,41 @@ impl Projection {
             Projection::Orthographic { .. } => true,
         }
     }
+
+    /// The depth range of the matrix built by `matrix`.
+    pub fn depth_range(self) -> DepthRange {
+        match self {
+            Projection::Perspective { .. } => DepthRange::NegativeOneToOne,
+            Projection::Orthographic { .. } => DepthRange::ZeroToOne,
+        }
+    }
+
+    /// Build the projection matrix for a viewport `Rect` in logical units. The aspect ratio is
+    /// computed in physical pixels so that it matches the renderer on HiDPI displays.
+    pub fn matrix<M: FromMatrix4>(self, rect: &Rect, framebuffer_scale: Vector2) -> M {
+        let aspect_ratio = rect.to_physical(framebuffer_scale).aspect_ratio();
+        match self {
+            Projection::Perspective { fovy } => perspective(fovy, aspect_ratio, 0.1, 100.0),
+            Projection::Orthographic { view_width } => {
+                let view_height = view_width / aspect_ratio;
+                orthographic(
+                    -view_width,
+                    view_width,
+                    -view_height,
+                    view_height,
+                    -view_width,
+                    view_width,
+                )
+            }
+        }
+    }
 }
 
 /// The gizmo context for a single frame.
 pub struct Gizmo<'a> {
     ui: &'a Ui,
+    rect: Cell<Option<Rect>>,
+    validation: Cell<Validation>,
 }
 
 impl<'a> Gizmo<'a> {
@@ -70,10 +168,21 @@ impl<'a> Gizmo<'a> {
         begin_frame(ui)
     }
 
-    pub fn builder(&'a self, view: &'a Matrix4, model: &'a mut Matrix4) -> Builder<'a> {
+    pub fn builder<V: AsMatrix4, M: AsMatrix4>(&'a self, view: &'a V, model: &'a mut M) -> Builder<'a> {
         Builder::new(self, view, model)
     }
 
+    /// Configure a gizmo without a model matrix, e.g. for
+    /// [`Builder::manipulate_transform`](struct.Builder.html#method.manipulate_transform).
+    pub fn view_builder<V: AsMatrix4>(&'a self, view: &'a V) -> Builder<'a> {
+        Builder::from_view(self, view)
+    }
+
+    /// Create the shared viewport state for this frame. See [`Viewport`].
+    pub fn viewport(&'a self, rect: Rect, view: &impl AsMatrix4, projection: Projection) -> Viewport<'a> {
+        Viewport::new(self, rect, view, projection)
+    }
+
     pub fn ui(&self) -> &'a Ui {
         self.ui
     }
@@ -83,6 +192,11 @@ impl<'a> Gizmo<'a> {
         set_draw_list(self);
     }
 
+    /// Select the draw list that subsequent gizmos, grids and cubes are drawn into.
+    pub fn set_draw_target(&self, target: DrawTarget) {
+        set_draw_target(self, target);
+    }
+
     /// Returns true if the mouse cursor is over any gizmo control (e.g. axis, plan, or screen component).
     pub fn is_over(&self) -> bool {
         is_over(self)
@@ -104,50 +218,109 @@ impl<'a> Gizmo<'a> {
         set_orthographic(self, is_orthographic)
     }
 
+    /// Returns the scale from logical (imgui) units to physical framebuffer pixels.
+    pub fn framebuffer_scale(&self) -> Vector2 {
+        framebuffer_scale(self.ui)
+    }
+
     /// Set the viewport for rendering. Set to the display size or combine with
     /// `set_draw_list` to render inside of a window.
     pub fn set_rect(&self, x: f32, y: f32, width: f32, height: f32) {
         set_rect(self, x, y, width, height)
     }
 
-    /// Render a gizmo for manipulating a transformation. See [`Gizmo::manipulate`](struct.Gizmo.html#method.manipulate).
+    /// Set the id of the gizmos drawn next, to tell apart several gizmos that are drawn at the
+    /// same time.
+    pub fn set_id(&self, id: i32) {
+        set_id(self, id)
+    }
+
+    /// Set how the inputs of `manipulate` are validated for the rest of the frame. Defaults to
+    /// `Validation::None`.
+    pub fn set_validation(&self, validation: Validation) {
+        self.validation.set(validation);
+    }
+
+    pub fn validation(&self) -> Validation {
+        self.validation.get()
+    }
+
+    /// Render a gizmo for manipulating a transformation. Returns true if `model` was changed.
+    /// Returns false without rendering the gizmo if validation is enabled and the inputs are
+    /// invalid.
     #[allow(clippy::too_many_arguments)]
     pub fn manipulate(
         &self,
-        view: &Matrix4,
-        projection: &Matrix4,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
         operation: Operation,
         mode: Mode,
-        model: &mut Matrix4,
+        model: &mut impl AsMatrix4,
         delta_matrix: Option<&mut Matrix4>,
         snap: Option<&mut Vector3>,
         local_bounds: Option<&mut [Vector3; 2]>,
         bounds_snap: Option<&mut Vector3>,
-    ) {
-        manipulate(
+    ) -> bool {
+        let valid = self.validation().check(|| {
+            validation::validate(view, projection, model, self.rect.get().as_ref())
+        });
+        valid.is_ok()
+            && manipulate(
+                self,
+                view.as_matrix4(),
+                projection.as_matrix4(),
+                operation as i32,
+                mode,
+                model.as_matrix4_mut(),
+                delta_matrix,
+                snap,
+                local_bounds,
+                bounds_snap,
+            )
+    }
+
+    /// Like `manipulate`, but always validates the inputs, including the rect set with
+    /// `set_rect`, and returns the reason they were rejected.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_manipulate(
+        &self,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
+        operation: Operation,
+        mode: Mode,
+        model: &mut impl AsMatrix4,
+        delta_matrix: Option<&mut Matrix4>,
+        snap: Option<&mut Vector3>,
+        local_bounds: Option<&mut [Vector3; 2]>,
+        bounds_snap: Option<&mut Vector3>,
+    ) -> Result<bool, ValidationError> {
+        self.validation().or_skip().check(|| {
+            validation::validate(view, projection, model, self.rect.get().as_ref())
+        })?;
+        Ok(manipulate(
             self,
-            view,
-            projection,
-            operation,
+            view.as_matrix4(),
+            projection.as_matrix4(),
+            operation as i32,
             mode,
-            model,
+            model.as_matrix4_mut(),
             delta_matrix,
             snap,
             local_bounds,
             bounds_snap,
-        )
+        ))
     }
 
     pub fn view_manipulate(
         &self,
-        view: &mut Matrix4,
+        view: &mut impl AsMatrix4,
         camera_distance: f32,
         position: Vector2,
         size: Vector2,
         background_color: u32,
     ) {
         unsafe {
-            ImGuizmo_ViewManipulate(view.as_mut_ptr() as *mut f32, camera_distance, ImVec2 {
+            ImGuizmo_ViewManipulate(view.as_matrix4_mut().as_mut_ptr() as *mut f32, camera_distance, ImVec2 {
                 x: position[0],
                 y: position[1],
             }, ImVec2 {
@@ -158,45 +331,19 @@ impl<'a> Gizmo<'a> {
     }
 
     /// Draw a cube for debugging with `manipulate`.
-    pub fn draw_cube(&self, view: &Matrix4, projection: &Matrix4, model: &Matrix4) {
-        draw_cube(self, view, projection, model)
+    pub fn draw_cube(&self, view: &impl AsMatrix4, projection: &impl AsMatrix4, model: &impl AsMatrix4) {
+        draw_cube(self, view.as_matrix4(), projection.as_matrix4(), model.as_matrix4())
     }
 
     /// Draw a grid for debugging.
-    pub fn draw_grid(&self, view: &Matrix4, projection: &Matrix4, model: &Matrix4, grid_size: f32) {
-        draw_grid(self, view, projection, model, grid_size)
-    }
-
-    fn prepare_projection(&self, windowed: bool, proj: Projection) -> Matrix4 {
-        let rect = if windowed {
-            self.set_draw_list();
-            Rect::from_window(self.ui())
-        } else {
-            Rect::from_display(self.ui())
-        };
-        let projection = match proj {
-            Projection::Perspective { fovy } => {
-                let aspect_ratio = rect.width / rect.height;
-                perspective(fovy, aspect_ratio, 0.1, 100.0)
-            }
-            Projection::Orthographic { view_width } => {
-                let width = rect.width;
-                let height = rect.height;
-                let view_height = view_width * height / width;
-                orthographic(
-                    -view_width,
-                    view_width,
-                    -view_height,
-                    view_height,
-                    -view_width,
-                    view_width,
-                )
-            }
-        };
-
-        self.set_orthographic(proj.is_orthographic());
-        self.set_rect(rect.x, rect.y, rect.width, rect.height);
-        projection
+    pub fn draw_grid(
+        &self,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
+        model: &impl AsMatrix4,
+        grid_size: f32,
+    ) {
+        draw_grid(self, view.as_matrix4(), projection.as_matrix4(), model.as_matrix4(), grid_size)
     }
 }
 
@@ -205,7 +352,11 @@ fn begin_frame<'a>(ui: &'a Ui) -> Gizmo<'a> {
     unsafe {
         ffi::ImGuizmo_BeginFrame();
     }
-    Gizmo { ui }
+    Gizmo {
+        ui,
+        rect: Cell::new(None),
+        validation: Cell::new(Validation::None),
+    }
 }
 
 /// Call inside of a window, before `manipulate` in order to draw a gizmo in that window.
@@ -215,6 +366,21 @@ fn set_draw_list<'a>(_frame: &Gizmo<'a>) {
     }
 }
 
+/// Select the draw list that subsequent gizmos, grids and cubes are drawn into.
+fn set_draw_target<'a>(_frame: &Gizmo<'a>, target: DrawTarget) {
+    unsafe {
+        ffi::ImGuizmo_SetDrawlist(target.as_ptr() as *mut ffi::ImDrawList);
+    }
+}
+
+/// Set the id of the gizmos drawn next, to tell apart several gizmos that are drawn at the
+/// same time.
+fn set_id<'a>(_frame: &Gizmo<'a>, id: i32) {
+    unsafe {
+        ffi::ImGuizmo_SetID(id);
+    }
+}
+
 /// Returns true if the mouse cursor is over any gizmo control (e.g. axis, plan, or screen component).
 fn is_over<'a>(_frame: &Gizmo<'a>) -> bool {
     unsafe { ffi::ImGuizmo_IsOver() }
@@ -233,33 +399,51 @@ fn enable<'a>(_frame: &Gizmo<'a>, enable: bool) {
     }
 }
 
-/// Helper function to decompose a matrix into its components.
+/// Returns the scale from logical (imgui) units to physical framebuffer pixels.
+fn framebuffer_scale(ui: &Ui) -> Vector2 {
+    let [x, y] = ui.io().display_framebuffer_scale;
+    // The scale is zero until the platform backend fills it in.
+    [
+        if x > 0.0 { x } else { 1.0 },
+        if y > 0.0 { y } else { 1.0 },
+    ]
+}
+
+/// Helper function to decompose a matrix into its components. The rotation is returned as XYZ
+/// Euler angles in degrees, computed like ImGuizmo does. See [`decompose::decompose`] for other
+/// rotation conventions and for angles that stay stable in gimbal lock.
 pub fn decompose_matrix_to_components(
-    matrix: &Matrix4,
-    translation: &mut Vector3,
-    rotation: &mut Vector3,
-    scale: &mut Vector3,
+    matrix: &impl AsMatrix4,
+    translation: &mut impl AsVector3,
+    rotation: &mut impl AsVector3,
+    scale: &mut impl AsVector3,
 ) {
-    unsafe {
-        ffi::ImGuizmo_DecomposeMatrixToComponents(matrix.as_ptr() as *const f32, translation.as_mut_ptr() as *mut f32, rotation.as_mut_ptr() as *mut f32, scale.as_mut_ptr() as *mut f32);
-    }
+    let (t, r, s) = decompose::decompose_imguizmo(matrix.as_matrix4());
+    *translation.as_vector3_mut() = t;
+    *rotation.as_vector3_mut() = r;
+    *scale.as_vector3_mut() = s;
 }
 
-/// Helper function to compose a matrix from it's components.
+/// Helper function to compose a matrix from it's components. The rotation is given as XYZ Euler
+/// angles in degrees. See [`decompose::recompose`] for other rotation conventions.
 pub fn recompose_matrix_from_components(
-    translation: &Vector3,
-    rotation: &Vector3,
-    scale: &Vector3,
-    matrix: &mut Matrix4,
+    translation: &impl AsVector3,
+    rotation: &impl AsVector3,
+    scale: &impl AsVector3,
+    matrix: &mut impl AsMatrix4,
 ) {
-    unsafe {
-        ffi::ImGuizmo_RecomposeMatrixFromComponents(translation.as_ptr() as *const f32, rotation.as_ptr() as *const f32, scale.as_ptr() as *const f32, matrix.as_mut_ptr() as *mut f32);
-    }
+    let rotation = decompose::Rotation::Euler {
+        angles: *rotation.as_vector3(),
+        order: decompose::EulerOrder::XYZ,
+        unit: decompose::AngleUnit::Degrees,
+    };
+    *matrix.as_matrix4_mut() = decompose::recompose(translation.as_vector3(), &rotation, scale.as_vector3());
 }
 
 /// Set the viewport for rendering. Set to the display size or combine with
 /// `set_draw_list` to render inside of a window.
-fn set_rect<'a>(_frame: &Gizmo<'a>, x: f32, y: f32, width: f32, height: f32) {
+fn set_rect<'a>(frame: &Gizmo<'a>, x: f32, y: f32, width: f32, height: f32) {
+    frame.rect.set(Some(Rect { x, y, width, height }));
     unsafe {
         ffi::ImGuizmo_SetRect(x, y, width, height);
     }
@@ -304,19 +488,20 @@ fn draw_grid<'a>(
 }
 
 /// Render a gizmo for manipulating a transformation. See [`Gizmo::manipulate`](struct.Gizmo.html#method.manipulate).
+/// `operation` holds ImGuizmo's operation bits.
 #[allow(clippy::too_many_arguments)]
 fn manipulate<'a>(
     _frame: &Gizmo<'a>,
     view: &Matrix4,
     projection: &Matrix4,
-    operation: Operation,
+    operation: i32,
     mode: Mode,
     model: &mut Matrix4,
     delta_matrix: Option<&mut Matrix4>,
     snap: Option<&mut Vector3>,
     local_bounds: Option<&mut [Vector3; 2]>,
     bounds_snap: Option<&mut Vector3>,
-) {
+) -> bool {
     unsafe {
         let delta_matrix = delta_matrix.map_or_else(ptr::null_mut, |v| v.as_mut_ptr() as _);
         let snap = snap.map_or_else(ptr::null_mut, |v| v.as_mut_ptr());
@@ -325,18 +510,118 @@ fn manipulate<'a>(
         ffi::ImGuizmo_Manipulate(
             view.as_ptr() as *const f32,
             projection.as_ptr() as *const f32,
-            operation as i32,
-            mode as i32,
+            operation,
+            match mode {
+                Mode::Parent => Mode::World,
+                mode => mode,
+            } as i32,
             model.as_mut_ptr() as *mut f32,
             delta_matrix,
             snap,
             local_bounds,
             bounds_snap,
-        );
+        )
+    }
+}
+
+/// The draw list a gizmo is rendered into.
+#[derive(Copy, Clone)]
+pub enum DrawTarget<'a> {
+    /// The draw list of the current window.
+    Window,
+    /// The foreground draw list of a viewport, drawn above all windows. `None` selects the
+    /// viewport of the current window.
+    Foreground(Option<&'a imgui::Viewport>),
+    /// The background draw list of a viewport, drawn below all windows. `None` selects the
+    /// viewport of the current window.
+    Background(Option<&'a imgui::Viewport>),
+    /// An explicit draw list, created with [`from_draw_list`](#method.from_draw_list) or
+    /// [`from_raw`](#method.from_raw).
+    DrawList(RawDrawList<'a>),
+}
+
+/// A pointer to an `ImDrawList` that is valid for `'a`. See [`DrawTarget::from_draw_list`].
+#[derive(Copy, Clone, Debug)]
+pub struct RawDrawList<'a> {
+    draw_list: *mut imgui::sys::ImDrawList,
+    _marker: PhantomData<&'a mut imgui::sys::ImDrawList>,
+}
+
+impl<'a> DrawTarget<'a> {
+    /// Target a draw list of imgui-rs, e.g. `ui.get_window_draw_list()`, for as long as it is
+    /// borrowed. Returns `None` for the draw list of a window other than the current one.
+    pub fn from_draw_list(draw_list: &'a imgui::DrawListMut<'_>) -> Option<Self> {
+        // `DrawListMut` does not expose its `ImDrawList`, but it is one of the draw lists of the
+        // current window. Find the one whose clip rect stack grows while `draw_list` pushes a
+        // clip rect; intersecting with an unbounded rect leaves its draw commands as they are.
+        let candidates = [
+            DrawTarget::Window,
+            DrawTarget::Foreground(None),
+            DrawTarget::Background(None),
+        ]
+        .map(|target| target.as_ptr());
+        let depth = |draw_list: *mut imgui::sys::ImDrawList| unsafe {
+            (*draw_list)._ClipRectStack.Size
+        };
+        let depths = candidates.map(depth);
+        let mut found = None;
+        draw_list.with_clip_rect_intersect([-f32::MAX; 2], [f32::MAX; 2], || {
+            found = candidates
+                .into_iter()
+                .zip(depths)
+                .find(|&(candidate, before)| depth(candidate) > before);
+        });
+        found.map(|(draw_list, _)| {
+            DrawTarget::DrawList(RawDrawList {
+                draw_list,
+                _marker: PhantomData,
+            })
+        })
     }
+
+    /// Target an explicit draw list by its underlying `ImDrawList`, e.g. one that is not
+    /// owned by imgui-rs. Prefer [`from_draw_list`](#method.from_draw_list).
+    ///
+    /// # Safety
+    ///
+    /// `draw_list` must point to a valid `ImDrawList` that is neither freed nor cleared for
+    /// `'a`, i.e. for as long as gizmos, grids and cubes may be drawn into it.
+    pub unsafe fn from_raw(draw_list: *mut imgui::sys::ImDrawList) -> Self {
+        DrawTarget::DrawList(RawDrawList {
+            draw_list,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the underlying `ImDrawList` for this target.
+    pub fn as_ptr(&self) -> *mut imgui::sys::ImDrawList {
+        unsafe {
+            match *self {
+                DrawTarget::Window => imgui::sys::igGetWindowDrawList(),
+                DrawTarget::Foreground(None) => imgui::sys::igGetForegroundDrawList_Nil(),
+                DrawTarget::Foreground(Some(viewport)) => {
+                    imgui::sys::igGetForegroundDrawList_ViewportPtr(viewport_ptr(viewport))
+                }
+                DrawTarget::Background(None) => imgui::sys::igGetBackgroundDrawList_Nil(),
+                DrawTarget::Background(Some(viewport)) => {
+                    imgui::sys::igGetBackgroundDrawList_ViewportPtr(viewport_ptr(viewport))
+                }
+                DrawTarget::DrawList(raw) => raw.draw_list,
+            }
+        }
+    }
+}
+
+/// `imgui::Viewport` shares its layout with `ImGuiViewport`.
+fn viewport_ptr(viewport: &imgui::Viewport) -> *mut imgui::sys::ImGuiViewport {
+    viewport as *const imgui::Viewport as *mut imgui::sys::ImGuiViewport
 }
 
 /// Build a viewport from a window or the whole display.
+///
+/// Rects are in logical (imgui) units, the same space as mouse positions and draw lists.
+/// Use [`to_physical`](#method.to_physical) and [`to_logical`](#method.to_logical) to convert
+/// to and from the physical pixels used by the renderer.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Rect {
@@ -359,6 +644,44 @@ impl Rect {
         }
     }
 
+    /// Creates a viewport `Rect` from the content region of the current window. Unlike
+    /// [`from_window`](#method.from_window), this excludes the title bar, menu bar and window padding.
+    pub fn from_content_region(ui: &Ui) -> Rect {
+        let [x, y] = ui.window_pos();
+        let [min_x, min_y] = ui.window_content_region_min();
+        let [max_x, max_y] = ui.window_content_region_max();
+        Rect {
+            x: x + min_x,
+            y: y + min_y,
+            width: max_x - min_x,
+            height: max_y - min_y,
+        }
+    }
+
+    /// Creates a viewport `Rect` from the last submitted item, e.g. right after `ui.image`.
+    pub fn from_last_item(ui: &Ui) -> Rect {
+        let [x, y] = ui.item_rect_min();
+        let [width, height] = ui.item_rect_size();
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a viewport `Rect` of the given size at the current cursor position.
+    pub fn from_cursor(ui: &Ui, size: Vector2) -> Rect {
+        let [x, y] = ui.cursor_screen_pos();
+        let [width, height] = size;
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
     /// Creates a viewport `Rect` from the display size.
     pub fn from_display<'ui>(ui: &Ui) -> Rect {
         let [width, height] = ui.io().display_size;
@@ -369,44 +692,137 @@ impl Rect {
             height,
         }
     }
+
+    /// Creates a viewport `Rect` covering an imgui platform viewport. Use this with
+    /// [`DrawTarget::Foreground`] and [`DrawTarget::Background`] in multi-viewport mode.
+    pub fn from_viewport(viewport: &imgui::Viewport) -> Rect {
+        let [x, y] = viewport.pos;
+        let [width, height] = viewport.size;
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a viewport `Rect` from a rect in physical pixels, e.g. the renderer's viewport.
+    pub fn from_physical(ui: &Ui, physical: Rect) -> Rect {
+        physical.to_logical(framebuffer_scale(ui))
+    }
+
+    /// Converts from logical (imgui) units to physical framebuffer pixels.
+    pub fn to_physical(&self, framebuffer_scale: Vector2) -> Rect {
+        let [sx, sy] = framebuffer_scale;
+        Rect {
+            x: self.x * sx,
+            y: self.y * sy,
+            width: self.width * sx,
+            height: self.height * sy,
+        }
+    }
+
+    /// Converts from physical framebuffer pixels to logical (imgui) units.
+    pub fn to_logical(&self, framebuffer_scale: Vector2) -> Rect {
+        let [sx, sy] = framebuffer_scale;
+        Rect {
+            x: self.x / sx,
+            y: self.y / sy,
+            width: self.width / sx,
+            height: self.height / sy,
+        }
+    }
+
+    /// Returns true if `point` lies inside of this rect.
+    pub fn contains(&self, point: Vector2) -> bool {
+        let [x, y] = point;
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Returns `width / height`.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width / self.height
+    }
 }
 
 /// Configure a gizmo for transformation manipulation.
 pub struct Builder<'a> {
     pub gizmo: &'a Gizmo<'a>,
     pub view: &'a Matrix4,
-    pub model: &'a mut Matrix4,
+    pub model: Option<&'a mut Matrix4>,
     pub projection: Projection,
     pub operation: Operation,
     pub windowed: bool,
+    pub rect: Option<Rect>,
+    pub draw_target: Option<DrawTarget<'a>>,
+    pub clip: bool,
+    pub viewport: Option<&'a Viewport<'a>>,
     pub mode: Mode,
     pub delta_matrix: Option<&'a mut Matrix4>,
     pub snap: Option<&'a mut Vector3>,
     pub local_bounds: Option<&'a mut [Vector3; 2]>,
     pub bounds_snap: Option<&'a mut Vector3>,
+    pub validation: Option<Validation>,
+    pub pivot: Pivot,
+    pub active: usize,
+    pub parent: Option<&'a Matrix4>,
+    pub id: Option<i32>,
+    pub drag_tracker: Option<&'a mut DragTracker>,
+    pub axis: Option<Axis>,
+    pub snap_config: Option<SnapConfig>,
+    pub snapping: Option<bool>,
+    pub surface_snap: Option<&'a mut SurfaceSnap<'a>>,
+    pub surface_alignment: Option<Axis>,
 }
 
 impl<'a> Builder<'a> {
-    pub fn new(
+    pub fn new<V: AsMatrix4, M: AsMatrix4>(
         gizmo: &'a Gizmo<'a>,
-        view: &'a Matrix4,
-        model: &'a mut Matrix4,
+        view: &'a V,
+        model: &'a mut M,
     ) -> Builder<'a> {
+        Builder::from_view(gizmo, view).with_model(model.as_matrix4_mut())
+    }
+
+    /// A builder without a model matrix. Set one with `with_model`, or use
+    /// `manipulate_transform`.
+    pub fn from_view<V: AsMatrix4>(gizmo: &'a Gizmo<'a>, view: &'a V) -> Builder<'a> {
         Builder {
             gizmo,
-            view,
-            model,
+            view: view.as_matrix4(),
+            model: None,
             projection: Projection::Perspective { fovy: 45.0 },
             operation: Operation::Rotate,
             windowed: false,
+            rect: None,
+            draw_target: None,
+            clip: false,
+            viewport: None,
             mode: Mode::Local,
             delta_matrix: None,
             snap: None,
             local_bounds: None,
             bounds_snap: None,
+            validation: None,
+            pivot: Pivot::BoundingBoxCenter,
+            active: 0,
+            parent: None,
+            id: None,
+            drag_tracker: None,
+            axis: None,
+            snap_config: None,
+            snapping: None,
+            surface_snap: None,
+            surface_alignment: None,
         }
     }
 
+    /// Set the matrix to manipulate.
+    pub fn with_model<T: Into<Option<&'a mut Matrix4>>>(mut self, model: T) -> Self {
+        self.model = model.into();
+        self
+    }
+
     /// Set the view projection. Defaults to `Perspective` with a `fovy` of `45` degrees.
     pub fn with_projection(mut self, projection: Projection) -> Self {
         self.projection = projection;
@@ -419,91 +835,582 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Constrain translations, rotations and scales to a single axis. Defaults to `None`.
+    pub fn with_axis<T: Into<Option<Axis>>>(mut self, axis: T) -> Self {
+        self.axis = axis.into();
+        self
+    }
+
+    /// Set the operation, mode and axis from `config`, e.g. as edited by
+    /// [`GizmoHotkeys`](hotkeys/struct.GizmoHotkeys.html). Snapping with the values of
+    /// `with_snap`, `with_bounds_snap` or `with_snap_config` is turned on and off by
+    /// `config.is_snapping()`, in place of the `enabled` flag and modifier of the snap config.
+    pub fn with_config(mut self, config: &hotkeys::GizmoConfig) -> Self {
+        self.snapping = Some(config.is_snapping());
+        self.with_operation(config.operation)
+            .with_mode(config.mode)
+            .with_axis(config.axis)
+    }
+
     /// Set to `true` to draw the Gizmo inside of the current window. Defaults to `false`.
     pub fn with_windowed(mut self, windowed: bool) -> Self {
         self.windowed = windowed;
         self
     }
 
+    /// Set the viewport used for rendering and picking. Defaults to the current window
+    /// when `windowed`, or the whole display otherwise.
+    pub fn with_rect<T: Into<Option<Rect>>>(mut self, rect: T) -> Self {
+        self.rect = rect.into();
+        self
+    }
+
+    /// Set the draw list to render into. Defaults to the current window when `windowed`, the
+    /// background draw list when clipping, or ImGuizmo's own full-screen overlay otherwise.
+    pub fn with_draw_target<T: Into<Option<DrawTarget<'a>>>>(mut self, draw_target: T) -> Self {
+        self.draw_target = draw_target.into();
+        self
+    }
+
+    /// Set to `true` to clip drawing and hover detection to the viewport `Rect`, e.g. inside of
+    /// a child window or scrolled region. Without a draw target, and when not `windowed`, this
+    /// draws into the background draw list instead of ImGuizmo's own overlay, which cannot be
+    /// clipped. Defaults to `false`.
+    pub fn with_clip(mut self, clip: bool) -> Self {
+        self.clip = clip;
+        self
+    }
+
+    /// Use a shared [`Viewport`] instead of preparing one for each call. The viewport's rect,
+    /// projection, draw target and clipping replace the ones set on this builder.
+    pub fn with_viewport<T: Into<Option<&'a Viewport<'a>>>>(mut self, viewport: T) -> Self {
+        self.viewport = viewport.into();
+        self
+    }
+
     /// Set the transformation matrix mode. Defaults to `Local`.
     pub fn with_mode(mut self, mode: Mode) -> Self {
         self.mode = mode;
         self
     }
 
-    pub fn with_delta_matrix<T: Into<Option<&'a mut Matrix4>>>(mut self, delta_matrix: T) -> Self {
-        self.delta_matrix = delta_matrix.into();
+    pub fn with_delta_matrix<M, T>(mut self, delta_matrix: T) -> Self
+    where
+        M: AsMatrix4 + 'a,
+        T: Into<Option<&'a mut M>>,
+    {
+        self.delta_matrix = delta_matrix.into().map(AsMatrix4::as_matrix4_mut);
         self
     }
 
-    pub fn with_snap<T: Into<Option<&'a mut Vector3>>>(mut self, snap: T) -> Self {
-        self.snap = snap.into();
+    pub fn with_snap<V, T>(mut self, snap: T) -> Self
+    where
+        V: AsVector3 + 'a,
+        T: Into<Option<&'a mut V>>,
+    {
+        self.snap = snap.into().map(AsVector3::as_vector3_mut);
         self
     }
 
-    pub fn with_local_bounds<T: Into<Option<&'a mut [Vector3; 2]>>>(
-        mut self,
-        local_bounds: T,
-    ) -> Self {
-        self.local_bounds = local_bounds.into();
+    /// Snap with the steps of `snap_config` for the current operation. Replaces the values
+    /// set with `with_snap` and `with_bounds_snap`.
+    pub fn with_snap_config<T: Into<Option<SnapConfig>>>(mut self, snap_config: T) -> Self {
+        self.snap_config = snap_config.into();
+        self
+    }
+
+    /// Snap translations to surfaces. While the gizmo is dragged, `surface_snap` is called
+    /// with the world space ray under the mouse and the model is moved to the hit it returns,
+    /// if any. The callback should ignore the model being moved.
+    pub fn with_surface_snap<F>(mut self, surface_snap: &'a mut F) -> Self
+    where
+        F: FnMut(&Ray) -> Option<SurfaceHit>,
+    {
+        self.surface_snap = Some(surface_snap);
+        self
+    }
+
+    /// Rotate the model so that its local `axis` points along the normal of the surface hits
+    /// of `with_surface_snap`. Defaults to `None`, which keeps the rotation.
+    pub fn with_surface_alignment<T: Into<Option<Axis>>>(mut self, axis: T) -> Self {
+        self.surface_alignment = axis.into();
         self
     }
 
-    pub fn with_bounds_snap<T: Into<Option<&'a mut Vector3>>>(mut self, bounds_snap: T) -> Self {
-        self.bounds_snap = bounds_snap.into();
+    pub fn with_local_bounds<V, T>(mut self, local_bounds: T) -> Self
+    where
+        V: AsVector3 + 'a,
+        T: Into<Option<&'a mut [V; 2]>>,
+    {
+        // `V` has the layout of `Vector3`, so an array of two has the layout of `[Vector3; 2]`.
+        self.local_bounds = local_bounds
+            .into()
+            .map(|bounds| unsafe { &mut *(bounds as *mut [V; 2] as *mut [Vector3; 2]) });
+        self
+    }
+
+    pub fn with_bounds_snap<V, T>(mut self, bounds_snap: T) -> Self
+    where
+        V: AsVector3 + 'a,
+        T: Into<Option<&'a mut V>>,
+    {
+        self.bounds_snap = bounds_snap.into().map(AsVector3::as_vector3_mut);
+        self
+    }
+
+    /// Set how the inputs of `manipulate` are validated. Defaults to the validation set on
+    /// the [`Gizmo`].
+    pub fn with_validation<T: Into<Option<Validation>>>(mut self, validation: T) -> Self {
+        self.validation = validation.into();
+        self
+    }
+
+    /// Set the world matrix of the parent of the model. The gizmo is shown at the model's world
+    /// transformation, `parent * model`, and the model is written back in the parent's space.
+    /// The delta matrix is in world space.
+    pub fn with_parent<T: Into<Option<&'a Matrix4>>>(mut self, parent: T) -> Self {
+        self.parent = parent.into();
+        self
+    }
+
+    /// Set the id of this gizmo, to tell it apart from other gizmos drawn at the same time.
+    pub fn with_id<T: Into<Option<i32>>>(mut self, id: T) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Report the drag lifecycle of `manipulate` and `manipulate_transform` to `drag_tracker`,
+    /// which also cancels drags with its cancel key. Not supported by `manipulate_many`, whose
+    /// models have no single initial matrix. See [`DragTracker`].
+    pub fn with_drag_tracker<T: Into<Option<&'a mut DragTracker>>>(mut self, drag_tracker: T) -> Self {
+        self.drag_tracker = drag_tracker.into();
+        self
+    }
+
+    /// Set where the gizmo of `manipulate_many` is placed. Defaults to `BoundingBoxCenter`.
+    pub fn with_pivot(mut self, pivot: Pivot) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    /// Set the index of the active target of `manipulate_many`, which the gizmo is oriented
+    /// like in `Local` mode. Defaults to `0`.
+    pub fn with_active(mut self, active: usize) -> Self {
+        self.active = active;
         self
     }
 
     /// Draw the transformation manipulation gizmo. Automates the usage of [set_draw_list](fn.set_draw_list.html),
     /// [set_rect](fn.set_rect.html), [set_orthographic](fn.set_orthographic.html), and [manipulate](fn.manipulate.html)
-    /// based on this `Gizmo`s attributes.
-    pub fn manipulate(self) {
-        let projection = self
-            .gizmo
-            .prepare_projection(self.windowed, self.projection);
-        manipulate(
-            self.gizmo,
-            self.view,
-            &projection,
-            self.operation,
+    /// based on this `Gizmo`s attributes. Returns true if the model matrix was changed, or false
+    /// if no model matrix was set or validation rejected the inputs.
+    pub fn manipulate(self) -> bool {
+        let validation = self.validation();
+        self.manipulate_validated(validation).unwrap_or(false)
+    }
+
+    /// Like `manipulate`, but always validates the inputs and returns the reason they were
+    /// rejected.
+    pub fn try_manipulate(self) -> Result<bool, ValidationError> {
+        let validation = self.validation().or_skip();
+        self.manipulate_validated(validation)
+    }
+
+    fn manipulate_validated(mut self, validation: Validation) -> Result<bool, ValidationError> {
+        let delta_matrix = self.delta_matrix.take();
+        let drag_tracker = self.drag_tracker.take();
+        let (gizmo, id) = (self.gizmo, self.id.unwrap_or(drag::DEFAULT_ID));
+        let model = match self.model.take() {
+            Some(model) => model,
+            None => return Ok(false),
+        };
+        let before = *model;
+        let changed = self.manipulate_matrix(model, delta_matrix, validation)?;
+        match drag_tracker {
+            Some(tracker) => Ok(tracker.update(gizmo, id, &before, model, changed) || changed),
+            None => Ok(changed),
+        }
+    }
+
+    /// Draw the transformation manipulation gizmo for `transform`. Translations and rotations
+    /// are applied to the quaternion directly instead of being decomposed from the resulting
+    /// matrix, so that the untouched components do not drift. Returns true if `transform`
+    /// was changed.
+    pub fn manipulate_transform(mut self, transform: &mut Transform) -> bool {
+        let mut model = transform.to_matrix();
+        let before = model;
+        let mut delta = math::IDENTITY;
+        let drag_tracker = self.drag_tracker.take();
+        let (gizmo, id) = (self.gizmo, self.id.unwrap_or(drag::DEFAULT_ID));
+        let operation = self.operation;
+        let user_delta = self.delta_matrix.take();
+        let parent = self.parent;
+        let validation = self.validation();
+        let changed = self
+            .manipulate_matrix(&mut model, Some(&mut delta), validation)
+            .unwrap_or(false);
+        if changed {
+            // Bring the world space delta into the parent's space.
+            let local_delta = match parent.map(|parent| (parent, math::inverse::<Matrix4>(parent))) {
+                Some((parent, Some(inverse))) => {
+                    math::multiply(&inverse, &math::multiply::<Matrix4>(&delta, parent))
+                }
+                _ => delta,
+            };
+            transform.apply_manipulation(operation, &local_delta, &model);
+        }
+        if let Some(user_delta) = user_delta {
+            *user_delta = delta;
+        }
+        if let Some(tracker) = drag_tracker {
+            let mut after = transform.to_matrix();
+            if tracker.update(gizmo, id, &before, &mut after, changed) {
+                *transform = Transform::from_matrix(&after);
+                return true;
+            }
+        }
+        changed
+    }
+
+    /// Draw a single gizmo for all of the `models`, placed according to the [`Pivot`]. The
+    /// change of the gizmo is applied to every model. Returns true if the models were changed.
+    ///
+    /// The gizmo stays where it was grabbed until the drag ends, even if the pivot moves.
+    /// `state` keeps it there across frames. When drawing several of these with the same
+    /// `state`, tell them apart with [`with_id`](Self::with_id).
+    ///
+    /// A drag tracker set with [`with_drag_tracker`](Self::with_drag_tracker) is not supported
+    /// here, and is ignored in release builds.
+    pub fn manipulate_many<M: AsMatrix4>(
+        self,
+        models: &mut [&mut M],
+        state: &mut SelectionState,
+    ) -> bool {
+        debug_assert!(
+            self.drag_tracker.is_none(),
+            "manipulate_many does not support a drag tracker"
+        );
+        if models.is_empty() {
+            return false;
+        }
+        let (gizmo, pivot) = (self.gizmo, self.pivot);
+        let id = self.id.unwrap_or(drag::DEFAULT_ID);
+        let frame = gizmo.ui().frame_count();
+        let parent = self.parent;
+        let inverse = match parent.map(math::inverse::<Matrix4>) {
+            Some(None) => return false,
+            inverse => inverse.flatten(),
+        };
+        let mut worlds: Vec<Matrix4> = match parent {
+            Some(parent) => models.iter().map(|model| math::multiply(parent, *model)).collect(),
+            None => Vec::new(),
+        };
+        let mut world_models: Vec<&mut Matrix4> = worlds.iter_mut().collect();
+
+        let before = match parent {
+            Some(_) => state.gizmo_matrix(frame, id, &world_models, pivot, self.active),
+            None => state.gizmo_matrix(frame, id, models, pivot, self.active),
+        };
+        let mut after = before;
+        let validation = self.validation();
+        let changed = self
+            .manipulate_world(&mut after, None, validation)
+            .unwrap_or(false);
+        state.end_frame(frame, id, gizmo.is_using(), &after);
+        if changed {
+            match inverse {
+                Some(inverse) => {
+                    selection::apply(&mut world_models, pivot, &before, &after);
+                    for (model, world) in models.iter_mut().zip(&world_models) {
+                        *model.as_matrix4_mut() = math::multiply(&inverse, *world);
+                    }
+                }
+                None => selection::apply(models, pivot, &before, &after),
+            }
+        }
+        changed
+    }
+
+    fn validation(&self) -> Validation {
+        self.validation.unwrap_or_else(|| self.gizmo.validation())
+    }
+
+    /// Manipulate `model` in the space of the parent, if any.
+    fn manipulate_matrix(
+        self,
+        model: &mut Matrix4,
+        delta_matrix: Option<&mut Matrix4>,
+        validation: Validation,
+    ) -> Result<bool, ValidationError> {
+        let parent = match self.parent {
+            Some(parent) => parent,
+            None => return self.manipulate_world(model, delta_matrix, validation),
+        };
+        let inverse: Matrix4 = match math::inverse(parent) {
+            Some(inverse) => inverse,
+            None => return Err(ValidationError::SingularParent),
+        };
+        let mut world = math::multiply(parent, model);
+        let changed = self.manipulate_world(&mut world, delta_matrix, validation)?;
+        if changed {
+            *model = math::multiply(&inverse, &world);
+        }
+        Ok(changed)
+    }
+
+    /// Manipulate the world matrix `model`, snapping translations to surfaces or to the
+    /// world grid of the snap config.
+    fn manipulate_world(
+        mut self,
+        model: &mut Matrix4,
+        mut delta_matrix: Option<&mut Matrix4>,
+        validation: Validation,
+    ) -> Result<bool, ValidationError> {
+        let prepared;
+        let viewport = match self.viewport {
+            Some(viewport) => viewport,
+            None => {
+                prepared = self.prepare_viewport();
+                &prepared
+            }
+        };
+        let surface_snap = self
+            .surface_snap
+            .take()
+            .filter(|_| self.operation == Operation::Translate);
+        let (gizmo, alignment, grid_snap) = (self.gizmo, self.surface_alignment, self.grid_snap());
+        if surface_snap.is_none() && grid_snap.is_none() {
+            return self.manipulate_frame(viewport, model, delta_matrix, validation);
+        }
+        // ImGuizmo snaps the distance moved, so snap after it moved the model.
+        let before = *model;
+        let changed = self.manipulate_frame(viewport, model, delta_matrix.as_deref_mut(), validation)?;
+        let hit = match surface_snap {
+            Some(surface_snap) if changed && gizmo.is_using() => {
+                viewport.mouse_ray().and_then(|ray| surface_snap(&ray))
+            }
+            _ => None,
+        };
+        match (hit, grid_snap) {
+            (Some(hit), _) => snap_to_surface(model, &hit, alignment),
+            (None, Some(grid_snap)) => {
+                let [x, y, z, _] = model[3];
+                let mut translation = [x, y, z];
+                grid_snap.snap_to_grid(&math::column3(&before[3]), &mut translation);
+                model[3] = [translation[0], translation[1], translation[2], 1.0];
+            }
+            (None, None) => return Ok(changed),
+        }
+        if let Some(delta_matrix) = delta_matrix {
+            *delta_matrix = match math::inverse::<Matrix4>(&before) {
+                Some(inverse) => math::multiply(model, &inverse),
+                None => math::IDENTITY,
+            };
+        }
+        Ok(*model != before)
+    }
+
+    /// The snap config if it snaps translations to a world space grid.
+    fn grid_snap(&self) -> Option<SnapConfig> {
+        self.snap_config.filter(|config| {
+            self.operation == Operation::Translate
+                && self.mode == Mode::World
+                && config.translation_origin.is_some()
+                && self.is_snapping(config)
+        })
+    }
+
+    /// Returns true if snapping with `config` is active, as set by `with_config` or else by
+    /// `config` itself.
+    fn is_snapping(&self, config: &SnapConfig) -> bool {
+        self.snapping.unwrap_or_else(|| config.is_active(self.gizmo.ui()))
+    }
+
+    /// Manipulate the world matrix `model` in the rotated frame of the parent in
+    /// `Mode::Parent`.
+    fn manipulate_frame(
+        self,
+        viewport: &Viewport,
+        model: &mut Matrix4,
+        delta_matrix: Option<&mut Matrix4>,
+        validation: Validation,
+    ) -> Result<bool, ValidationError> {
+        let frame = match (self.mode, self.parent) {
+            (Mode::Parent, Some(parent)) => {
+                let mut rotation: Matrix4 = math::orthonormalize(parent);
+                rotation[3] = [0.0, 0.0, 0.0, 1.0];
+                rotation
+            }
+            _ => {
+                let view = self.view;
+                return self.manipulate_view(viewport, view, model, delta_matrix, validation);
+            }
+        };
+        // ImGuizmo only knows local and world axes. Rotate the world so that the parent's
+        // axes become the world axes; the gizmo looks the same on screen.
+        let inverse_frame: Matrix4 = math::transpose(&frame);
+        let view: Matrix4 = math::multiply(self.view, &frame);
+        let mut rotated: Matrix4 = math::multiply(&inverse_frame, model);
+        let mut rotated_delta = math::IDENTITY;
+        let want_delta = delta_matrix.is_some();
+        let changed = self.manipulate_view(
+            viewport,
+            &view,
+            &mut rotated,
+            want_delta.then_some(&mut rotated_delta),
+            validation,
+        )?;
+        if changed {
+            *model = math::multiply(&frame, &rotated);
+        }
+        if let Some(delta_matrix) = delta_matrix {
+            *delta_matrix = math::multiply(&frame, &math::multiply::<Matrix4>(&rotated_delta, &inverse_frame));
+        }
+        Ok(changed)
+    }
+
+    fn manipulate_view(
+        self,
+        viewport: &Viewport,
+        view: &Matrix4,
+        model: &mut Matrix4,
+        delta_matrix: Option<&mut Matrix4>,
+        validation: Validation,
+    ) -> Result<bool, ValidationError> {
+        if let Some(id) = self.id {
+            self.gizmo.set_id(id);
+        }
+        let (mut snap, mut bounds_snap) = ([0.0; 3], [0.0; 3]);
+        let (snap, bounds_snap) = match self.snap_config {
+            Some(config) => {
+                let active = self.is_snapping(&config) && self.grid_snap().is_none();
+                let values = config.values(self.operation).filter(|_| active);
+                let bounds = config.bounds.filter(|_| active);
+                (
+                    values.map(|values| {
+                        snap = values;
+                        &mut snap
+                    }),
+                    bounds.map(|bounds| {
+                        bounds_snap = bounds;
+                        &mut bounds_snap
+                    }),
+                )
+            }
+            None if self.snapping == Some(false) => (None, None),
+            None => (self.snap, self.bounds_snap),
+        };
+        viewport.manipulate(
+            view,
+            self.operation.bits(self.axis),
             self.mode,
-            self.model,
-            self.delta_matrix,
-            self.snap,
+            model,
+            delta_matrix,
+            snap,
             self.local_bounds,
-            self.bounds_snap,
-        );
+            bounds_snap,
+            validation,
+        )
     }
 
     /// Draw a grid for debugging.
     pub fn draw_grid(self, grid_size: f32) -> Self {
-        let projection = self
-            .gizmo
-            .prepare_projection(self.windowed, self.projection);
-        self.gizmo
-            .draw_grid(self.view, &projection, &self.model, grid_size);
+        match self.viewport {
+            Some(viewport) => viewport.draw_grid(self.model_or_identity(), grid_size),
+            None => self.prepare_viewport().draw_grid(self.model_or_identity(), grid_size),
+        }
         self
     }
 
     /// Draw a cube for debugging with `manipulate`.
     pub fn draw_cube(self) -> Self {
-        let projection = self
-            .gizmo
-            .prepare_projection(self.windowed, self.projection);
-        self.gizmo.draw_cube(self.view, &projection, &self.model);
+        match self.viewport {
+            Some(viewport) => viewport.draw_cube(self.model_or_identity()),
+            None => self.prepare_viewport().draw_cube(self.model_or_identity()),
+        }
         self
     }
+
+    fn model_or_identity(&self) -> &Matrix4 {
+        const IDENTITY: Matrix4 = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        self.model.as_deref().unwrap_or(&IDENTITY)
+    }
+
+    /// Prepare a viewport from this builder's attributes.
+    fn prepare_viewport(&self) -> Viewport<'a> {
+        let draw_target = match self.draw_target {
+            None if self.windowed => Some(DrawTarget::Window),
+            draw_target => draw_target,
+        };
+        let rect = self.rect.unwrap_or_else(|| match draw_target {
+            Some(DrawTarget::Foreground(Some(viewport)))
+            | Some(DrawTarget::Background(Some(viewport))) => Rect::from_viewport(viewport),
+            _ if self.windowed => Rect::from_window(self.gizmo.ui()),
+            _ => Rect::from_display(self.gizmo.ui()),
+        });
+        Viewport::new(self.gizmo, rect, self.view, self.projection)
+            .with_draw_target(draw_target)
+            .with_clip(self.clip)
+    }
+}
+
+/// Move the world matrix `model` to the surface `hit`, turning its local `alignment` axis
+/// towards the surface normal.
+fn snap_to_surface(model: &mut Matrix4, hit: &SurfaceHit, alignment: Option<Axis>) {
+    if let (Some(axis), Some(normal)) = (alignment, hit.normal) {
+        let rotation = math::rotation_between(&math::column3(&model[axis as usize]), &normal);
+        model[3] = [0.0, 0.0, 0.0, 1.0];
+        *model = math::multiply(&rotation, model);
+    }
+    model[3] = [hit.position[0], hit.position[1], hit.position[2], 1.0];
+}
+
+/// Run `f` with `draw_list` clipped to `rect`. Runs `f` unclipped if `draw_list` is null.
+fn with_clip_rect<R>(draw_list: *mut imgui::sys::ImDrawList, rect: &Rect, f: impl FnOnce() -> R) -> R {
+    if draw_list.is_null() {
+        return f();
+    }
+    unsafe {
+        imgui::sys::ImDrawList_PushClipRect(
+            draw_list,
+            imgui::sys::ImVec2::new(rect.x, rect.y),
+            imgui::sys::ImVec2::new(rect.x + rect.width, rect.y + rect.height),
+            true,
+        );
+    }
+    let result = f();
+    unsafe {
+        imgui::sys::ImDrawList_PopClipRect(draw_list);
+    }
+    result
+}
+
+/// Run `f` with the mouse moved off-screen so that ImGuizmo does not detect any hovers.
+fn without_mouse<R>(f: impl FnOnce() -> R) -> R {
+    unsafe {
+        let io = &mut *imgui::sys::igGetIO();
+        let mouse_pos = io.MousePos;
+        io.MousePos = imgui::sys::ImVec2::new(-f32::MAX, -f32::MAX);
+        let result = f();
+        io.MousePos = mouse_pos;
+        result
+    }
 }
 
 /// Helper for creating a frustum.
-pub fn frustum(
+pub fn frustum<M: FromMatrix4>(
     left: f32,
     right: f32,
     bottom: f32,
     top: f32,
     znear: f32,
     zfar: f32,
-) -> [[f32; 4]; 4] {
+) -> M {
     let mut m: [[f32; 4]; 4] = Default::default();
 
     let t1 = 2.0 * znear;
@@ -531,25 +1438,25 @@ pub fn frustum(
     m[3][2] = (-t1 * zfar) / t4;
     m[3][3] = 0.0;
 
-    m
+    M::from_matrix4(m)
 }
 
 /// Helper for creating a perspective projection matrix.
-pub fn perspective(fovy_degrees: f32, aspect_ratio: f32, znear: f32, zfar: f32) -> [[f32; 4]; 4] {
+pub fn perspective<M: FromMatrix4>(fovy_degrees: f32, aspect_ratio: f32, znear: f32, zfar: f32) -> M {
     let ymax = znear * (fovy_degrees * std::f32::consts::PI / 180.0).tan();
     let xmax = ymax * aspect_ratio;
     frustum(-xmax, xmax, -ymax, ymax, znear, zfar)
 }
 
 /// Helper for creating an orthographic projection matrix.
-pub fn orthographic(
+pub fn orthographic<M: FromMatrix4>(
     left: f32,
     right: f32,
     bottom: f32,
     top: f32,
     znear: f32,
     zfar: f32,
-) -> [[f32; 4]; 4] {
+) -> M {
     let mut m: [[f32; 4]; 4] = Default::default();
 
     m[0][0] = 2.0 / (right - left);
@@ -572,5 +1479,48 @@ pub fn orthographic(
     m[3][2] = znear / (znear - zfar);
     m[3][3] = 1.0;
 
-    m
-}
\ No newline at end of file
+    M::from_matrix4(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_count(draw_list: *mut imgui::sys::ImDrawList) -> i32 {
+        unsafe { (*draw_list).CmdBuffer.Size }
+    }
+
+    #[test]
+    fn draw_targets_find_the_draw_lists_of_imgui_rs() {
+        let mut context = imgui::Context::create();
+        context.io_mut().display_size = [100.0, 100.0];
+        context.fonts().build_rgba32_texture();
+        let ui = context.new_frame();
+
+        let background = DrawTarget::Background(None).as_ptr();
+        let commands = command_count(background);
+        let draw_list = ui.get_background_draw_list();
+        let target = DrawTarget::from_draw_list(&draw_list).map(|target| target.as_ptr());
+        assert_eq!(target, Some(background));
+        assert_eq!(command_count(background), commands);
+        drop(draw_list);
+
+        let foreground = DrawTarget::Foreground(None).as_ptr();
+        let draw_list = ui.get_foreground_draw_list();
+        draw_list.add_line([0.0, 0.0], [10.0, 10.0], [1.0; 4]).build();
+        let commands = command_count(foreground);
+        let target = DrawTarget::from_draw_list(&draw_list).map(|target| target.as_ptr());
+        assert_eq!(target, Some(foreground));
+        assert_eq!(command_count(foreground), commands);
+        drop(draw_list);
+
+        ui.window("Window").build(|| {
+            let draw_list = ui.get_window_draw_list();
+            let target = DrawTarget::from_draw_list(&draw_list).map(|target| target.as_ptr());
+            assert_eq!(target, Some(DrawTarget::Window.as_ptr()));
+            ui.child_window("Child").build(|| {
+                assert!(DrawTarget::from_draw_list(&draw_list).is_none());
+            });
+        });
+    }
+}
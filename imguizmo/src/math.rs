@@ -0,0 +1,463 @@
+//! Matrix and vector utilities for [`Matrix4`], [`Vector3`] and [`Vector4`].
+//!
+//! Matrices are column-major, the way ImGuizmo expects them: `m[3]` holds the translation and
+//! `m[column][row]` addresses a single component. Vectors are column vectors, so `multiply(a, b)`
+//! applies `b` first.
+//!
+//! ```rust
+//! use imguizmo::{math, Matrix4, Vector3};
+//!
+//! let view: Matrix4 = math::look_at_rh(&[8.0, 8.0, 8.0], &[0.0, 0.0, 0.0], &[0.0, 1.0, 0.0]);
+//! let camera: Matrix4 = math::affine_inverse(&view).unwrap();
+//! let eye: Vector3 = math::transform_point(&camera, &[0.0, 0.0, 0.0]);
+//! # for (a, b) in eye.iter().zip([8.0, 8.0, 8.0]) {
+//! #     assert!((a - b).abs() < 1e-4);
+//! # }
+//! ```
+pub use crate::{frustum, orthographic, perspective};
+
+use crate::{AsMatrix4, AsVector3, FromMatrix4, FromVector3, Matrix4, Vector3, Vector4};
+
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+pub const IDENTITY: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Column-major product `a * b`, i.e. `b` is applied first.
+pub fn multiply<M: FromMatrix4>(a: &impl AsMatrix4, b: &impl AsMatrix4) -> M {
+    M::from_matrix4(product(a.as_matrix4(), b.as_matrix4()))
+}
+
+pub fn transpose<M: FromMatrix4>(m: &impl AsMatrix4) -> M {
+    let m = m.as_matrix4();
+    let mut t = [[0.0; 4]; 4];
+    for (column, t_column) in t.iter_mut().enumerate() {
+        for (row, value) in t_column.iter_mut().enumerate() {
+            *value = m[row][column];
+        }
+    }
+    M::from_matrix4(t)
+}
+
+/// General 4x4 inverse, or `None` if `m` is singular.
+pub fn inverse<M: FromMatrix4>(m: &impl AsMatrix4) -> Option<M> {
+    invert(m.as_matrix4()).map(M::from_matrix4)
+}
+
+/// Inverse of an affine matrix, i.e. one whose last row is `[0, 0, 0, 1]` such as a model or
+/// view matrix. Cheaper than [`inverse`]. Returns `None` if `m` is singular.
+pub fn affine_inverse<M: FromMatrix4>(m: &impl AsMatrix4) -> Option<M> {
+    let m = m.as_matrix4();
+    let [x, y, z] = [column3(&m[0]), column3(&m[1]), column3(&m[2])];
+    // The rows of the inverse of [x y z] are the cross products of its columns over the
+    // determinant.
+    let rows = [cross(&y, &z), cross(&z, &x), cross(&x, &y)];
+    let det = dot(&x, &rows[0]);
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    let rows = rows.map(|row| scale(&row, 1.0 / det));
+
+    let mut inv = IDENTITY;
+    for (column, inv_column) in inv.iter_mut().take(3).enumerate() {
+        for (row, value) in inv_column.iter_mut().take(3).enumerate() {
+            *value = rows[row][column];
+        }
+    }
+    let translation = column3(&m[3]);
+    for (row, r) in rows.iter().enumerate() {
+        inv[3][row] = -dot(r, &translation);
+    }
+    Some(M::from_matrix4(inv))
+}
+
+/// `m * v` for a homogeneous vector.
+pub fn transform(m: &impl AsMatrix4, v: &Vector4) -> Vector4 {
+    let m = m.as_matrix4();
+    let mut r = [0.0; 4];
+    for (row, value) in r.iter_mut().enumerate() {
+        *value = (0..4).map(|k| m[k][row] * v[k]).sum();
+    }
+    r
+}
+
+/// Transform a point, including the translation and the perspective divide.
+pub fn transform_point<V: FromVector3>(m: &impl AsMatrix4, point: &impl AsVector3) -> V {
+    let p = point.as_vector3();
+    let [x, y, z, w] = transform(m, &[p[0], p[1], p[2], 1.0]);
+    V::from_vector3(if w != 0.0 && w != 1.0 {
+        [x / w, y / w, z / w]
+    } else {
+        [x, y, z]
+    })
+}
+
+/// Transform a direction, ignoring the translation.
+pub fn transform_vector<V: FromVector3>(m: &impl AsMatrix4, vector: &impl AsVector3) -> V {
+    let v = vector.as_vector3();
+    let [x, y, z, _] = transform(m, &[v[0], v[1], v[2], 0.0]);
+    V::from_vector3([x, y, z])
+}
+
+/// Right-handed view matrix looking from `eye` at `target`, i.e. the camera looks down -Z.
+/// This is the convention of ImGuizmo's examples and of OpenGL.
+pub fn look_at_rh<M: FromMatrix4>(
+    eye: &impl AsVector3,
+    target: &impl AsVector3,
+    up: &impl AsVector3,
+) -> M {
+    let (eye, target) = (eye.as_vector3(), target.as_vector3());
+    M::from_matrix4(look_to(eye, &sub(eye, target), up.as_vector3()))
+}
+
+/// Left-handed view matrix looking from `eye` at `target`, i.e. the camera looks down +Z.
+pub fn look_at_lh<M: FromMatrix4>(
+    eye: &impl AsVector3,
+    target: &impl AsVector3,
+    up: &impl AsVector3,
+) -> M {
+    let (eye, target) = (eye.as_vector3(), target.as_vector3());
+    M::from_matrix4(look_to(eye, &sub(target, eye), up.as_vector3()))
+}
+
+/// Orthogonalize and normalize the basis vectors of `m` with Gram-Schmidt, keeping the
+/// direction of the X axis, the plane of the X and Y axes, and the translation.
+pub fn orthonormalize<M: FromMatrix4>(m: &impl AsMatrix4) -> M {
+    let mut m = *m.as_matrix4();
+    let x = normalize(&column3(&m[0]));
+    let y = column3(&m[1]);
+    let y = normalize(&sub(&y, &scale(&x, dot(&x, &y))));
+    let z = column3(&m[2]);
+    let z = normalize(&sub(&sub(&z, &scale(&x, dot(&x, &z))), &scale(&y, dot(&y, &z))));
+    for (column, axis) in m.iter_mut().zip([x, y, z]) {
+        *column = [axis[0], axis[1], axis[2], 0.0];
+    }
+    M::from_matrix4(m)
+}
+
+/// View matrix whose Z axis is `z`.
+fn look_to(eye: &Vector3, z: &Vector3, up: &Vector3) -> Matrix4 {
+    let z = normalize(z);
+    let x = normalize(&cross(up, &z));
+    let y = cross(&z, &x);
+    [
+        [x[0], y[0], z[0], 0.0],
+        [x[1], y[1], z[1], 0.0],
+        [x[2], y[2], z[2], 0.0],
+        [-dot(&x, eye), -dot(&y, eye), -dot(&z, eye), 1.0],
+    ]
+}
+
+pub(crate) fn column3(v: &Vector4) -> Vector3 {
+    [v[0], v[1], v[2]]
+}
+
+pub(crate) fn add(a: &Vector3, b: &Vector3) -> Vector3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: &Vector3, b: &Vector3) -> Vector3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(v: &Vector3, s: f32) -> Vector3 {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+pub(crate) fn dot(a: &Vector3, b: &Vector3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: &Vector3, b: &Vector3) -> Vector3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn length(v: &Vector3) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// `v` scaled to unit length, or `v` itself if it is zero.
+pub(crate) fn normalize(v: &Vector3) -> Vector3 {
+    let length = length(v);
+    if length > f32::EPSILON {
+        scale(v, 1.0 / length)
+    } else {
+        *v
+    }
+}
+
+/// The shortest rotation that turns the direction `from` into the direction `to`.
+pub(crate) fn rotation_between(from: &Vector3, to: &Vector3) -> Matrix4 {
+    let (from, to) = (normalize(from), normalize(to));
+    let c = dot(&from, &to);
+    if c < -1.0 + 1e-6 {
+        // Half a turn around any axis perpendicular to `from`.
+        let other = if from[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+        let u = normalize(&cross(&from, &other));
+        let mut m = IDENTITY;
+        for (j, column) in m.iter_mut().take(3).enumerate() {
+            for (i, value) in column.iter_mut().take(3).enumerate() {
+                *value = 2.0 * u[i] * u[j] - if i == j { 1.0 } else { 0.0 };
+            }
+        }
+        return m;
+    }
+    // Rodrigues' formula: c I + [v]x + v vᵀ / (1 + c).
+    let v = cross(&from, &to);
+    let k = 1.0 / (1.0 + c);
+    [
+        [c + v[0] * v[0] * k, v[2] + v[0] * v[1] * k, -v[1] + v[0] * v[2] * k, 0.0],
+        [-v[2] + v[1] * v[0] * k, c + v[1] * v[1] * k, v[0] + v[1] * v[2] * k, 0.0],
+        [v[1] + v[2] * v[0] * k, -v[0] + v[2] * v[1] * k, c + v[2] * v[2] * k, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// The precisions the matrix functions below are shared between, `f32` for ImGuizmo and `f64`
+/// for [`double`](crate::double).
+pub(crate) trait Scalar:
+    Copy
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + Sum
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn is_finite(self) -> bool;
+    fn sin_cos(self) -> (Self, Self);
+}
+
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {$(
+        impl Scalar for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            fn is_finite(self) -> bool {
+                <$t>::is_finite(self)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                <$t>::sin_cos(self)
+            }
+        }
+    )*};
+}
+
+impl_scalar!(f32, f64);
+
+pub(crate) fn translation<T: Scalar>(t: &[T; 3]) -> [[T; 4]; 4] {
+    let (o, l) = (T::ZERO, T::ONE);
+    [[l, o, o, o], [o, l, o, o], [o, o, l, o], [t[0], t[1], t[2], l]]
+}
+
+/// Rotation of `angle` radians around the x, y or z `axis`.
+pub(crate) fn rotation_axis<T: Scalar>(axis: usize, angle: T) -> [[T; 4]; 4] {
+    let (s, c) = angle.sin_cos();
+    let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+    let mut m = translation(&[T::ZERO; 3]);
+    m[i][i] = c;
+    m[i][j] = s;
+    m[j][i] = -s;
+    m[j][j] = c;
+    m
+}
+
+/// [`multiply`] in any precision.
+pub(crate) fn product<T: Scalar>(a: &[[T; 4]; 4], b: &[[T; 4]; 4]) -> [[T; 4]; 4] {
+    let mut m = [[T::ZERO; 4]; 4];
+    for (column, b_column) in m.iter_mut().zip(b) {
+        for (row, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    m
+}
+
+/// [`inverse`] in any precision.
+pub(crate) fn invert<T: Scalar>(m: &[[T; 4]; 4]) -> Option<[[T; 4]; 4]> {
+    let a = |c: usize, r: usize| m[c][r];
+
+    let s0 = a(0, 0) * a(1, 1) - a(1, 0) * a(0, 1);
+    let s1 = a(0, 0) * a(1, 2) - a(1, 0) * a(0, 2);
+    let s2 = a(0, 0) * a(1, 3) - a(1, 0) * a(0, 3);
+    let s3 = a(0, 1) * a(1, 2) - a(1, 1) * a(0, 2);
+    let s4 = a(0, 1) * a(1, 3) - a(1, 1) * a(0, 3);
+    let s5 = a(0, 2) * a(1, 3) - a(1, 2) * a(0, 3);
+
+    let c5 = a(2, 2) * a(3, 3) - a(3, 2) * a(2, 3);
+    let c4 = a(2, 1) * a(3, 3) - a(3, 1) * a(2, 3);
+    let c3 = a(2, 1) * a(3, 2) - a(3, 1) * a(2, 2);
+    let c2 = a(2, 0) * a(3, 3) - a(3, 0) * a(2, 3);
+    let c1 = a(2, 0) * a(3, 2) - a(3, 0) * a(2, 2);
+    let c0 = a(2, 0) * a(3, 1) - a(3, 0) * a(2, 1);
+
+    let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+    if det == T::ZERO || !det.is_finite() {
+        return None;
+    }
+    let inv = T::ONE / det;
+
+    Some([
+        [
+            (a(1, 1) * c5 - a(1, 2) * c4 + a(1, 3) * c3) * inv,
+            (-a(0, 1) * c5 + a(0, 2) * c4 - a(0, 3) * c3) * inv,
+            (a(3, 1) * s5 - a(3, 2) * s4 + a(3, 3) * s3) * inv,
+            (-a(2, 1) * s5 + a(2, 2) * s4 - a(2, 3) * s3) * inv,
+        ],
+        [
+            (-a(1, 0) * c5 + a(1, 2) * c2 - a(1, 3) * c1) * inv,
+            (a(0, 0) * c5 - a(0, 2) * c2 + a(0, 3) * c1) * inv,
+            (-a(3, 0) * s5 + a(3, 2) * s2 - a(3, 3) * s1) * inv,
+            (a(2, 0) * s5 - a(2, 2) * s2 + a(2, 3) * s1) * inv,
+        ],
+        [
+            (a(1, 0) * c4 - a(1, 1) * c2 + a(1, 3) * c0) * inv,
+            (-a(0, 0) * c4 + a(0, 1) * c2 - a(0, 3) * c0) * inv,
+            (a(3, 0) * s4 - a(3, 1) * s2 + a(3, 3) * s0) * inv,
+            (-a(2, 0) * s4 + a(2, 1) * s2 - a(2, 3) * s0) * inv,
+        ],
+        [
+            (-a(1, 0) * c3 + a(1, 1) * c1 - a(1, 2) * c0) * inv,
+            (a(0, 0) * c3 - a(0, 1) * c1 + a(0, 2) * c0) * inv,
+            (-a(3, 0) * s3 + a(3, 1) * s1 - a(3, 2) * s0) * inv,
+            (a(2, 0) * s3 - a(2, 1) * s1 + a(2, 2) * s0) * inv,
+        ],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_close<T: Scalar + Into<f64>>(a: &[[T; 4]; 4], b: &[[T; 4]; 4]) {
+        for (a, b) in a.as_flattened().iter().zip(b.as_flattened()) {
+            let (a, b): (f64, f64) = ((*a).into(), (*b).into());
+            assert!((a - b).abs() < 1e-5, "{a:?} != {b:?}");
+        }
+    }
+
+    fn assert_close(a: &Vector3, b: &Vector3) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-5, "{a:?} != {b:?}");
+        }
+    }
+
+    /// A sheared, scaled and translated matrix with a projective row. Its components are
+    /// exact in binary, so that sums of its columns are too.
+    fn sample<T: Scalar + From<f32>>() -> [[T; 4]; 4] {
+        [
+            [2.0, 0.5, -1.0, 0.125],
+            [0.0, 3.0, 0.25, 0.0],
+            [1.0, -0.5, 1.5, -0.25],
+            [4.0, -5.0, 6.0, 1.0],
+        ]
+        .map(|column| column.map(T::from))
+    }
+
+    fn inverse_round_trips<T: Scalar + From<f32> + Into<f64>>() {
+        let identity = translation(&[T::ZERO; 3]);
+        let m = sample::<T>();
+        let inverse = invert(&m).unwrap();
+        assert_matrix_close(&product(&m, &inverse), &identity);
+        assert_matrix_close(&product(&inverse, &m), &identity);
+
+        // The third column is the sum of the first two.
+        let mut singular = m;
+        singular[2] = [0, 1, 2, 3].map(|row| m[0][row] + m[1][row]);
+        assert!(invert(&singular).is_none());
+        // A scale of zero.
+        let mut flat = m;
+        flat[1] = [T::ZERO; 4];
+        assert!(invert(&flat).is_none());
+    }
+
+    #[test]
+    fn inverse_round_trips_in_single_precision() {
+        inverse_round_trips::<f32>();
+        // Without the projective row, the cheaper affine inverse agrees.
+        let mut affine = sample::<f32>();
+        for column in &mut affine[..3] {
+            column[3] = 0.0;
+        }
+        let general: Matrix4 = inverse(&affine).unwrap();
+        let cheap: Matrix4 = affine_inverse(&affine).unwrap();
+        assert_matrix_close(&cheap, &general);
+    }
+
+    #[test]
+    fn inverse_round_trips_in_double_precision() {
+        inverse_round_trips::<f64>();
+    }
+
+    fn rotation_axis_is_right_handed<T: Scalar + From<f32> + Into<f64>>() {
+        let quarter = T::from(std::f32::consts::FRAC_PI_2);
+        // A quarter turn around each axis turns the next axis into the one after it.
+        for axis in 0..3 {
+            let m = rotation_axis(axis, quarter);
+            let (next, after) = ((axis + 1) % 3, (axis + 2) % 3);
+            let mut expected = translation(&[T::ZERO; 3]);
+            expected[next] = [T::ZERO; 4];
+            expected[next][after] = T::ONE;
+            expected[after] = [T::ZERO; 4];
+            expected[after][next] = -T::ONE;
+            assert_matrix_close(&m, &expected);
+            // The inverse of a rotation is its negation.
+            let back = rotation_axis(axis, -quarter);
+            assert_matrix_close(&product(&m, &back), &translation(&[T::ZERO; 3]));
+        }
+    }
+
+    #[test]
+    fn rotation_axis_is_right_handed_in_single_precision() {
+        rotation_axis_is_right_handed::<f32>();
+    }
+
+    #[test]
+    fn rotation_axis_is_right_handed_in_double_precision() {
+        rotation_axis_is_right_handed::<f64>();
+    }
+
+    #[test]
+    fn look_at_handedness() {
+        let (eye, target, up) = ([0.0, 0.0, 5.0], [0.0; 3], [0.0, 1.0, 0.0]);
+        let rh: Matrix4 = look_at_rh(&eye, &target, &up);
+        let lh: Matrix4 = look_at_lh(&eye, &target, &up);
+
+        // The target is in front of the camera, down -Z or +Z.
+        let t: Vector3 = transform_point(&rh, &target);
+        assert_close(&t, &[0.0, 0.0, -5.0]);
+        let t: Vector3 = transform_point(&lh, &target);
+        assert_close(&t, &[0.0, 0.0, 5.0]);
+        // Up stays up, and right is +X from the point of view of the camera.
+        for view in [&rh, &lh] {
+            let u: Vector3 = transform_vector(view, &up);
+            assert_close(&u, &[0.0, 1.0, 0.0]);
+        }
+        let r: Vector3 = transform_vector(&rh, &[1.0, 0.0, 0.0]);
+        assert_close(&r, &[1.0, 0.0, 0.0]);
+        let r: Vector3 = transform_vector(&lh, &[-1.0, 0.0, 0.0]);
+        assert_close(&r, &[1.0, 0.0, 0.0]);
+        // Both are rigid, so the eye goes to the origin.
+        for view in [&rh, &lh] {
+            let e: Vector3 = transform_point(view, &eye);
+            assert_close(&e, &[0.0; 3]);
+            let camera: Matrix4 = affine_inverse(view).unwrap();
+            assert_close(&column3(&camera[3]), &eye);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Drawing in 3D in a viewport, clipped to its frustum. Drawing goes to the viewport's draw
+//! target, or to the background draw list behind all windows without one.
+use crate::{
+    math, with_clip_rect, AsVector3, DepthRange, DrawTarget, Matrix4, Vector2, Vector4, Viewport,
+};
+
+use imgui::sys::{ImDrawList, ImVec2};
+use imgui::ImColor32;
+
+/// The smallest clip space `w` of a visible point, which keeps points at or behind the eye
+/// from being divided by zero or flipped.
+const MIN_W: f32 = 1e-5;
+
+impl<'a> Viewport<'a> {
+    /// The screen position of the world space `point`, or `None` if it is behind the near
+    /// plane. Points beside the viewport rect are returned outside of it.
+    pub fn world_to_screen(&self, point: &impl AsVector3) -> Option<Vector2> {
+        let clip = self.to_clip(point);
+        inside_near(&clip, self.depth_range()).then(|| self.clip_to_screen(&clip))
+    }
+
+    /// Draw a line from `a` to `b`. The part of the line outside of the view frustum, including
+    /// any part behind the camera, is not drawn.
+    pub fn draw_line_3d(
+        &self,
+        a: &impl AsVector3,
+        b: &impl AsVector3,
+        color: impl Into<ImColor32>,
+        thickness: f32,
+    ) {
+        if let Some((a, b)) = clip_line(self.to_clip(a), self.to_clip(b), self.depth_range()) {
+            let (a, b) = (self.clip_to_screen(&a), self.clip_to_screen(&b));
+            let color = color.into().to_bits();
+            self.draw(|draw_list| unsafe {
+                imgui::sys::ImDrawList_AddLine(draw_list, vec2(a), vec2(b), color, thickness);
+            });
+        }
+    }
+
+    /// Draw a dot of `radius` pixels at `point`, if it is inside of the view frustum.
+    pub fn draw_point_3d(&self, point: &impl AsVector3, color: impl Into<ImColor32>, radius: f32) {
+        let clip = self.to_clip(point);
+        if inside_frustum(&clip, self.depth_range()) {
+            let center = self.clip_to_screen(&clip);
+            let color = color.into().to_bits();
+            self.draw(|draw_list| unsafe {
+                imgui::sys::ImDrawList_AddCircleFilled(draw_list, vec2(center), radius, color, 0);
+            });
+        }
+    }
+
+    /// Draw `text` with its top left corner at `point`, if it is inside of the view frustum.
+    pub fn draw_text_3d(&self, point: &impl AsVector3, color: impl Into<ImColor32>, text: &str) {
+        let clip = self.to_clip(point);
+        if inside_frustum(&clip, self.depth_range()) {
+            let position = self.clip_to_screen(&clip);
+            let color = color.into().to_bits();
+            let range = text.as_bytes().as_ptr_range();
+            self.draw(|draw_list| unsafe {
+                imgui::sys::ImDrawList_AddText_Vec2(
+                    draw_list,
+                    vec2(position),
+                    color,
+                    range.start as *const _,
+                    range.end as *const _,
+                );
+            });
+        }
+    }
+
+    fn to_clip(&self, point: &impl AsVector3) -> Vector4 {
+        let view_projection: Matrix4 = math::multiply(self.projection_matrix(), self.view());
+        let [x, y, z] = *point.as_vector3();
+        math::transform(&view_projection, &[x, y, z, 1.0])
+    }
+
+    fn depth_range(&self) -> DepthRange {
+        self.projection().depth_range()
+    }
+
+    fn clip_to_screen(&self, [x, y, _, w]: &Vector4) -> Vector2 {
+        let rect = self.rect();
+        [
+            rect.x + (x / w * 0.5 + 0.5) * rect.width,
+            rect.y + (0.5 - y / w * 0.5) * rect.height,
+        ]
+    }
+
+    /// Run `f` with the draw list of this viewport clipped to its rect. Draws behind all
+    /// windows when no draw target was set, like ImGuizmo's own overlay, which is not exposed.
+    fn draw(&self, f: impl FnOnce(*mut ImDrawList)) {
+        let draw_list = self
+            .draw_target()
+            .unwrap_or(DrawTarget::Background(None))
+            .as_ptr();
+        with_clip_rect(draw_list, &self.rect(), || f(draw_list));
+    }
+}
+
+/// The signed distances of a clip space point to the planes of the frustum. The point is
+/// inside where all of them are positive.
+fn plane_distances([x, y, z, w]: &Vector4, depth_range: DepthRange) -> [f32; 7] {
+    let near = match depth_range {
+        DepthRange::NegativeOneToOne => w + z,
+        DepthRange::ZeroToOne => *z,
+    };
+    [w + x, w - x, w + y, w - y, near, w - z, w - MIN_W]
+}
+
+fn inside_near(clip: &Vector4, depth_range: DepthRange) -> bool {
+    let distances = plane_distances(clip, depth_range);
+    distances[4] >= 0.0 && distances[6] >= 0.0
+}
+
+fn inside_frustum(clip: &Vector4, depth_range: DepthRange) -> bool {
+    plane_distances(clip, depth_range).iter().all(|distance| *distance >= 0.0)
+}
+
+/// Clip the clip space line from `a` to `b` to the frustum, with Liang-Barsky. Returns `None`
+/// if no part of the line is inside.
+fn clip_line(a: Vector4, b: Vector4, depth_range: DepthRange) -> Option<(Vector4, Vector4)> {
+    let (mut start, mut end) = (0.0f32, 1.0f32);
+    let distances = |clip: &Vector4| plane_distances(clip, depth_range);
+    for (da, db) in distances(&a).into_iter().zip(distances(&b)) {
+        match (da < 0.0, db < 0.0) {
+            (true, true) => return None,
+            (true, false) => start = start.max(da / (da - db)),
+            (false, true) => end = end.min(da / (da - db)),
+            (false, false) => {}
+        }
+    }
+    if start > end {
+        return None;
+    }
+    let lerp = |t: f32| [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+    Some((lerp(start), lerp(end)))
+}
+
+fn vec2([x, y]: Vector2) -> ImVec2 {
+    ImVec2::new(x, y)
+}
@@ -0,0 +1,369 @@
+//! World space rays under the mouse, for picking and snapping.
+//!
+//! A [`Ray`] is generated the way ImGuizmo generates its own, and intersected with
+//! [`Shape`]s or with single primitives. Distances along a ray are in world units.
+//!
+//! ```rust
+//! use imguizmo::picking::{pick_nearest, Ray, Shape};
+//!
+//! let ray = Ray::new([0.0, 0.0, 10.0], [0.0, 0.0, -1.0]);
+//! let objects = [
+//!     ("far", Shape::Sphere { center: [0.0, 0.0, -5.0], radius: 1.0 }),
+//!     ("near", Shape::Aabb([[-1.0; 3], [1.0; 3]])),
+//!     ("missed", Shape::Sphere { center: [5.0, 0.0, 0.0], radius: 1.0 }),
+//! ];
+//!
+//! let (name, distance) = pick_nearest(&ray, &objects, |ray, (_, shape)| ray.intersect(shape)).unwrap();
+//! assert_eq!((name.0, distance), ("near", 9.0));
+//! ```
+use crate::{math, AsMatrix4, AsVector3, Matrix4, Rect, Vector2, Vector3};
+
+use imgui::Ui;
+
+/// A ray with a normalized `direction`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    pub direction: Vector3,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3, direction: Vector3) -> Self {
+        Ray {
+            origin,
+            direction: math::normalize(&direction),
+        }
+    }
+
+    /// The world space ray through the screen `point` of a viewport `rect`, the same way
+    /// ImGuizmo computes its camera ray. Returns `None` if `view * projection` is singular.
+    ///
+    /// Like ImGuizmo, the origin is unprojected at a depth of `0` in normalized device
+    /// coordinates. That is the near plane of projections with a
+    /// [`DepthRange::ZeroToOne`](crate::DepthRange::ZeroToOne), but lies beyond the near plane
+    /// with [`DepthRange::NegativeOneToOne`](crate::DepthRange::NegativeOneToOne), e.g. for
+    /// [`perspective`](crate::perspective). Objects closer than that can't be picked.
+    pub fn from_screen(
+        point: Vector2,
+        rect: &Rect,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
+    ) -> Option<Ray> {
+        let view_projection: Matrix4 = math::multiply(projection, view);
+        let inverse: Matrix4 = math::inverse(&view_projection)?;
+        let x = (point[0] - rect.x) / rect.width * 2.0 - 1.0;
+        let y = (1.0 - (point[1] - rect.y) / rect.height) * 2.0 - 1.0;
+        let unproject = |z: f32| {
+            let [px, py, pz, w] = math::transform(&inverse, &[x, y, z, 1.0]);
+            [px / w, py / w, pz / w]
+        };
+        let origin = unproject(0.0);
+        let end = unproject(1.0 - f32::EPSILON);
+        Some(Ray::new(origin, math::sub(&end, &origin)))
+    }
+
+    /// The world space ray under the mouse in a viewport `rect`. See `from_screen`.
+    pub fn from_mouse(
+        ui: &Ui,
+        rect: &Rect,
+        view: &impl AsMatrix4,
+        projection: &impl AsMatrix4,
+    ) -> Option<Ray> {
+        Ray::from_screen(ui.io().mouse_pos, rect, view, projection)
+    }
+
+    /// The point at `distance` along the ray.
+    pub fn at(&self, distance: f32) -> Vector3 {
+        math::add(&self.origin, &math::scale(&self.direction, distance))
+    }
+
+    /// The distance to the nearest point of `shape` in front of the ray, if it is hit.
+    pub fn intersect(&self, shape: &Shape) -> Option<f32> {
+        match shape {
+            Shape::Aabb(bounds) => self.intersect_aabb(bounds),
+            Shape::Obb(model, bounds) => self.intersect_obb(model, bounds),
+            Shape::Sphere { center, radius } => self.intersect_sphere(center, *radius),
+            Shape::Plane { point, normal } => self.intersect_plane(point, normal),
+            Shape::Triangle(triangle) => self.intersect_triangle(triangle),
+        }
+    }
+
+    /// Intersect axis-aligned `bounds`, given as `[min, max]`. A ray starting inside the bounds
+    /// hits them where it leaves.
+    pub fn intersect_aabb(&self, bounds: &[Vector3; 2]) -> Option<f32> {
+        slabs(&self.origin, &self.direction, bounds)
+    }
+
+    /// Intersect the `bounds` of an object in its own space, placed by its `model` matrix.
+    pub fn intersect_obb(&self, model: &impl AsMatrix4, bounds: &[Vector3; 2]) -> Option<f32> {
+        let inverse: Matrix4 = math::inverse(model)?;
+        // The direction is not normalized in the object's space, so that distances along it
+        // stay the same as in world space.
+        let origin = math::transform_point(&inverse, &self.origin);
+        let direction = math::transform_vector(&inverse, &self.direction);
+        slabs(&origin, &direction, bounds)
+    }
+
+    /// Intersect the sphere around `center`. A ray starting inside the sphere hits it where it
+    /// leaves.
+    pub fn intersect_sphere(&self, center: &impl AsVector3, radius: f32) -> Option<f32> {
+        let offset = math::sub(&self.origin, center.as_vector3());
+        let b = math::dot(&offset, &self.direction);
+        let c = math::dot(&offset, &offset) - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [-b - root, -b + root].into_iter().find(|&distance| distance >= 0.0)
+    }
+
+    /// Intersect the plane through `point` facing `normal`, from either side.
+    pub fn intersect_plane(&self, point: &impl AsVector3, normal: &impl AsVector3) -> Option<f32> {
+        let normal = normal.as_vector3();
+        let denominator = math::dot(&self.direction, normal);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+        let distance = math::dot(&math::sub(point.as_vector3(), &self.origin), normal) / denominator;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// Intersect a triangle from either side.
+    pub fn intersect_triangle(&self, [a, b, c]: &[Vector3; 3]) -> Option<f32> {
+        // Möller–Trumbore.
+        let (ab, ac) = (math::sub(b, a), math::sub(c, a));
+        let p = math::cross(&self.direction, &ac);
+        let determinant = math::dot(&ab, &p);
+        if determinant.abs() <= f32::EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let offset = math::sub(&self.origin, a);
+        let u = math::dot(&offset, &p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = math::cross(&offset, &ab);
+        let v = math::dot(&self.direction, &q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = math::dot(&ac, &q) * inverse;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+/// A pickable shape in world space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// Axis-aligned bounds, given as `[min, max]`.
+    Aabb([Vector3; 2]),
+    /// The bounds `[min, max]` of an object in its own space, placed by its model matrix.
+    Obb(Matrix4, [Vector3; 2]),
+    Sphere { center: Vector3, radius: f32 },
+    Plane { point: Vector3, normal: Vector3 },
+    Triangle([Vector3; 3]),
+}
+
+/// The nearest of `objects` hit by `ray`, according to `intersect`, with its distance.
+pub fn pick_nearest<T>(
+    ray: &Ray,
+    objects: impl IntoIterator<Item = T>,
+    mut intersect: impl FnMut(&Ray, &T) -> Option<f32>,
+) -> Option<(T, f32)> {
+    objects
+        .into_iter()
+        .filter_map(|object| intersect(ray, &object).map(|distance| (object, distance)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// The slab test of a ray against axis-aligned `bounds`.
+fn slabs(origin: &Vector3, direction: &Vector3, [min, max]: &[Vector3; 2]) -> Option<f32> {
+    let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+    for axis in 0..3 {
+        if direction[axis].abs() <= f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let inverse = 1.0 / direction[axis];
+        let a = (min[axis] - origin[axis]) * inverse;
+        let b = (max[axis] - origin[axis]) * inverse;
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    if near > far || far < 0.0 {
+        return None;
+    }
+    Some(if near >= 0.0 { near } else { far })
+}
+
+/// A callback that finds the surface under a ray, see
+/// [`Builder::with_surface_snap`](crate::Builder::with_surface_snap).
+pub type SurfaceSnap<'a> = dyn FnMut(&Ray) -> Option<SurfaceHit> + 'a;
+
+/// A point on a surface found by a snapping callback, see
+/// [`Builder::with_surface_snap`](crate::Builder::with_surface_snap).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SurfaceHit {
+    pub position: Vector3,
+    /// The surface normal at `position`, if the model should be aligned to it.
+    pub normal: Option<Vector3>,
+}
+
+impl SurfaceHit {
+    pub fn new<T: Into<Option<Vector3>>>(position: Vector3, normal: T) -> Self {
+        SurfaceHit {
+            position,
+            normal: normal.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Vector3, b: &Vector3) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-4, "{a:?} != {b:?}");
+        }
+    }
+
+    const UNIT_BOX: [Vector3; 2] = [[-1.0; 3], [1.0; 3]];
+
+    /// Rays along -Z from `[x, y, 10]`.
+    fn down(x: f32, y: f32) -> Ray {
+        Ray::new([x, y, 10.0], [0.0, 0.0, -1.0])
+    }
+
+    #[test]
+    fn aabb_slabs() {
+        assert_eq!(down(0.0, 0.0).intersect_aabb(&UNIT_BOX), Some(9.0));
+        assert_eq!(down(0.0, 2.0).intersect_aabb(&UNIT_BOX), None);
+        // Parallel to the X and Y slabs, inside and on their faces.
+        assert_eq!(down(1.0, -1.0).intersect_aabb(&UNIT_BOX), Some(9.0));
+        // A ray starting inside hits where it leaves.
+        let inside = Ray::new([0.5, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        assert_eq!(inside.intersect_aabb(&UNIT_BOX), Some(0.5));
+        // The box is behind the ray.
+        let away = Ray::new([0.0, 0.0, 10.0], [0.0, 0.0, 1.0]);
+        assert_eq!(away.intersect_aabb(&UNIT_BOX), None);
+        // A diagonal ray misses the corner.
+        let diagonal = Ray::new([3.0, 0.0, 0.0], [-1.0, 1.0, 0.0]);
+        assert_eq!(diagonal.intersect_aabb(&UNIT_BOX), None);
+    }
+
+    #[test]
+    fn obb_keeps_world_distances() {
+        let scale = [
+            [2.0, 0.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0, 0.0],
+            [0.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        let rotation: Matrix4 = math::multiply(&math::rotation_axis(2, 0.7), &scale);
+        let model: Matrix4 = math::multiply(&math::translation(&[0.0, 0.0, -3.0]), &rotation);
+        // The box is 4 units deep around `z = -3`.
+        let distance = down(0.0, 0.0).intersect_obb(&model, &UNIT_BOX).unwrap();
+        assert!((distance - 11.0).abs() < 1e-4, "{distance}");
+        assert_eq!(down(3.0, 3.0).intersect_obb(&model, &UNIT_BOX), None);
+        assert_eq!(down(0.0, 0.0).intersect_obb(&[[0.0; 4]; 4], &UNIT_BOX), None);
+    }
+
+    #[test]
+    fn spheres() {
+        let center = [0.0, 0.0, 0.0];
+        assert_eq!(down(0.0, 0.0).intersect_sphere(&center, 2.0), Some(8.0));
+        assert_eq!(down(0.0, 2.0).intersect_sphere(&center, 2.0), Some(10.0));
+        assert_eq!(down(0.0, 2.5).intersect_sphere(&center, 2.0), None);
+        let inside = Ray::new([0.0, 1.0, 0.0], [0.0, 1.0, 0.0]);
+        assert_eq!(inside.intersect_sphere(&center, 2.0), Some(1.0));
+        let away = Ray::new([0.0, 0.0, 10.0], [0.0, 0.0, 1.0]);
+        assert_eq!(away.intersect_sphere(&center, 2.0), None);
+    }
+
+    #[test]
+    fn planes() {
+        let (point, normal) = ([0.0, 0.0, 1.0], [0.0, 0.0, 1.0]);
+        assert_eq!(down(3.0, 4.0).intersect_plane(&point, &normal), Some(9.0));
+        // From the back side.
+        let below = Ray::new([0.0, 0.0, -1.0], [0.0, 0.0, 1.0]);
+        assert_eq!(below.intersect_plane(&point, &normal), Some(2.0));
+        // Behind the ray, and parallel to the plane.
+        let away = Ray::new([0.0, 0.0, 10.0], [0.0, 0.0, 1.0]);
+        assert_eq!(away.intersect_plane(&point, &normal), None);
+        let parallel = Ray::new([0.0, 0.0, 5.0], [1.0, 0.0, 0.0]);
+        assert_eq!(parallel.intersect_plane(&point, &normal), None);
+        let inside = Ray::new([0.0, 0.0, 1.0], [1.0, 0.0, 0.0]);
+        assert_eq!(inside.intersect_plane(&point, &normal), None);
+    }
+
+    #[test]
+    fn triangles() {
+        let triangle = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        assert_eq!(down(0.25, 0.25).intersect_triangle(&triangle), Some(10.0));
+        // On the edges and corners.
+        for [x, y] in [[0.5, 0.5], [0.0, 0.5], [0.5, 0.0], [0.0, 0.0], [1.0, 0.0]] {
+            assert_eq!(down(x, y).intersect_triangle(&triangle), Some(10.0), "{x} {y}");
+        }
+        assert_eq!(down(0.6, 0.6).intersect_triangle(&triangle), None);
+        assert_eq!(down(-0.1, 0.5).intersect_triangle(&triangle), None);
+        // From the back side, behind the ray and parallel to the triangle.
+        let below = Ray::new([0.25, 0.25, -1.0], [0.0, 0.0, 1.0]);
+        assert_eq!(below.intersect_triangle(&triangle), Some(1.0));
+        let away = Ray::new([0.25, 0.25, 10.0], [0.0, 0.0, 1.0]);
+        assert_eq!(away.intersect_triangle(&triangle), None);
+        let parallel = Ray::new([-1.0, 0.25, 0.0], [1.0, 0.0, 0.0]);
+        assert_eq!(parallel.intersect_triangle(&triangle), None);
+    }
+
+    #[test]
+    fn pick_nearest_ignores_misses() {
+        let objects = [
+            Shape::Sphere { center: [0.0, 0.0, -5.0], radius: 1.0 },
+            Shape::Aabb(UNIT_BOX),
+            Shape::Plane { point: [0.0, 0.0, 20.0], normal: [0.0, 0.0, 1.0] },
+            Shape::Triangle([[5.0, 0.0, 5.0], [6.0, 0.0, 5.0], [5.0, 1.0, 5.0]]),
+        ];
+        let ray = down(0.0, 0.0);
+        let nearest = pick_nearest(&ray, objects.iter().enumerate(), |ray, (_, shape)| {
+            ray.intersect(shape)
+        });
+        assert_eq!(nearest.map(|((index, _), distance)| (index, distance)), Some((1, 9.0)));
+        let nothing = pick_nearest(&down(9.0, 9.0), &objects[..2], |ray, shape| {
+            ray.intersect(shape)
+        });
+        assert_eq!(nothing, None);
+    }
+
+    #[test]
+    fn screen_rays_start_at_depth_zero() {
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+        };
+        let up = [0.0, 1.0, 0.0];
+
+        // Depths of 0..1 start the ray on the near plane. `orthographic` looks along +Z.
+        let view: Matrix4 = math::look_at_lh(&[0.0, 0.0, -10.0], &[0.0; 3], &up);
+        let orthographic: Matrix4 = crate::orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 100.0);
+        let ray = Ray::from_screen([150.0, 25.0], &rect, &view, &orthographic).unwrap();
+        assert_close(&ray.origin, &[1.0, 0.5, -9.0]);
+        assert_close(&ray.direction, &[0.0, 0.0, 1.0]);
+
+        // Depths of -1..1 start it between the near and the far plane.
+        let view: Matrix4 = math::look_at_rh(&[0.0, 0.0, 10.0], &[0.0; 3], &up);
+        let perspective: Matrix4 = crate::frustum(-0.2, 0.2, -0.1, 0.1, 0.1, 100.0);
+        let ray = Ray::from_screen([100.0, 50.0], &rect, &view, &perspective).unwrap();
+        let near = 2.0 * 100.0 * 0.1 / (100.0 + 0.1);
+        assert_close(&ray.origin, &[0.0, 0.0, 10.0 - near]);
+        assert_close(&ray.direction, &[0.0, 0.0, -1.0]);
+
+        assert_eq!(Ray::from_screen([0.0, 0.0], &rect, &view, &[[0.0; 4]; 4]), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Manipulating many matrices with a single gizmo. See
+//! [`Builder::manipulate_many`](crate::Builder::manipulate_many).
+use crate::math::{self, column3};
+use crate::{AsMatrix4, Matrix4};
+
+use std::collections::HashMap;
+
+/// The point a multi-target gizmo is placed at, and that rotations and scales are applied
+/// around.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Pivot {
+    /// The center of the bounding box of the targets' origins.
+    #[default]
+    BoundingBoxCenter,
+    /// The average of the targets' origins.
+    MedianPoint,
+    /// The origin of the active target.
+    ActiveElement,
+    /// The gizmo is placed at the bounding box center, but every target is rotated and scaled
+    /// around its own origin.
+    IndividualOrigins,
+}
+
+/// The state of the gizmos of [`Builder::manipulate_many`](crate::Builder::manipulate_many)
+/// across frames. Keep one alive for as long as the gizmos, like a
+/// [`DragTracker`](crate::DragTracker); gizmos are told apart by the id set with
+/// [`Builder::with_id`](crate::Builder::with_id).
+#[derive(Clone, Debug, Default)]
+pub struct SelectionState {
+    /// The gizmo matrix ImGuizmo returned on the previous frame of a drag and that frame, by
+    /// gizmo id. It is used instead of the pivot of the targets until the drag ends, so that
+    /// the gizmo does not move when the pivot does, e.g. the bounding box center during a
+    /// rotation. ImGuizmo also computes scales from the matrix at the start of the drag rather
+    /// than incrementally, so the previous result is needed to get the change of this frame.
+    drags: HashMap<i32, (i32, Matrix4)>,
+}
+
+impl SelectionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The matrix the gizmo with the given `id` is drawn with in `frame`: the result of the
+    /// previous frame while it is dragged, otherwise the pivot position, oriented like the
+    /// `active` target.
+    pub(crate) fn gizmo_matrix<M: AsMatrix4>(
+        &self,
+        frame: i32,
+        id: i32,
+        models: &[&mut M],
+        pivot: Pivot,
+        active: usize,
+    ) -> Matrix4 {
+        // A drag that did not go on in the previous frame ended while the gizmo was hidden.
+        match self.drags.get(&id) {
+            Some(&(dragged, matrix)) if frame.wrapping_sub(dragged) == 1 => matrix,
+            _ => pivot_matrix(models, pivot, active),
+        }
+    }
+
+    /// Keep the gizmo matrix `after` of `frame` for the next one while the gizmo with the
+    /// given `id` is dragged. Forgets ended drags, including those of hidden gizmos.
+    pub(crate) fn end_frame(&mut self, frame: i32, id: i32, is_using: bool, after: &Matrix4) {
+        self.drags
+            .retain(|&other, &mut (dragged, _)| other != id && frame.wrapping_sub(dragged) <= 1);
+        if is_using {
+            self.drags.insert(id, (frame, *after));
+        }
+    }
+}
+
+/// The pivot position, oriented like the `active` target.
+fn pivot_matrix<M: AsMatrix4>(models: &[&mut M], pivot: Pivot, active: usize) -> Matrix4 {
+    let active = models[active.min(models.len() - 1)].as_matrix4();
+    let origins = models.iter().map(|model| column3(&model.as_matrix4()[3]));
+
+    let position = match pivot {
+        Pivot::ActiveElement => column3(&active[3]),
+        Pivot::MedianPoint => {
+            let sum = origins.fold([0.0; 3], |sum, origin| math::add(&sum, &origin));
+            math::scale(&sum, 1.0 / models.len() as f32)
+        }
+        Pivot::BoundingBoxCenter | Pivot::IndividualOrigins => {
+            let (min, max) = origins.fold(
+                ([f32::MAX; 3], [f32::MIN; 3]),
+                |(min, max), origin| {
+                    (
+                        [0, 1, 2].map(|i| min[i].min(origin[i])),
+                        [0, 1, 2].map(|i| max[i].max(origin[i])),
+                    )
+                },
+            );
+            math::scale(&math::add(&min, &max), 0.5)
+        }
+    };
+
+    let mut matrix: Matrix4 = math::orthonormalize(active);
+    matrix[3] = [position[0], position[1], position[2], 1.0];
+    matrix
+}
+
+/// Apply the change from `before` to `after` of the gizmo matrix to all `models`.
+pub(crate) fn apply<M: AsMatrix4>(models: &mut [&mut M], pivot: Pivot, before: &Matrix4, after: &Matrix4) {
+    let delta: Matrix4 = match math::inverse::<Matrix4>(before) {
+        Some(inverse) => math::multiply(after, &inverse),
+        None => return,
+    };
+
+    for model in models.iter_mut() {
+        let model = model.as_matrix4_mut();
+        *model = match pivot {
+            Pivot::IndividualOrigins => {
+                // Move every origin by the pivot's translation, but rotate and scale around
+                // the origin itself.
+                let pivot_position = column3(&before[3]);
+                let offset = math::sub(&math::transform_point(&delta, &pivot_position), &pivot_position);
+                let origin = column3(&model[3]);
+                let mut local = delta;
+                local[3] = [0.0, 0.0, 0.0, 1.0];
+                let mut result: Matrix4 = math::multiply(&local, &math::translation(&math::scale(&origin, -1.0)));
+                result = math::multiply(&result, model);
+                let position = math::add(&origin, &offset);
+                result[3] = [position[0], position[1], position[2], 1.0];
+                result
+            }
+            _ => math::multiply(&delta, model),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models() -> [Matrix4; 3] {
+        [[0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [2.0, 6.0, 0.0]].map(|t| math::translation(&t))
+    }
+
+    fn position(matrix: &Matrix4) -> [f32; 3] {
+        column3(&matrix[3])
+    }
+
+    #[test]
+    fn pivots_place_the_gizmo() {
+        let mut models = models();
+        let models: Vec<&mut Matrix4> = models.iter_mut().collect();
+        let state = SelectionState::new();
+        let gizmo = |pivot| position(&state.gizmo_matrix(0, 0, &models, pivot, 2));
+        assert_eq!(gizmo(Pivot::BoundingBoxCenter), [2.0, 3.0, 0.0]);
+        assert_eq!(gizmo(Pivot::IndividualOrigins), [2.0, 3.0, 0.0]);
+        assert_eq!(gizmo(Pivot::MedianPoint), [2.0, 2.0, 0.0]);
+        assert_eq!(gizmo(Pivot::ActiveElement), [2.0, 6.0, 0.0]);
+    }
+
+    #[test]
+    fn drags_keep_the_gizmo_until_they_end() {
+        let mut models = models();
+        let models: Vec<&mut Matrix4> = models.iter_mut().collect();
+        let pivot = Pivot::BoundingBoxCenter;
+        let dragged = math::translation(&[9.0, 9.0, 9.0]);
+        let mut state = SelectionState::new();
+
+        state.end_frame(10, 1, true, &dragged);
+        assert_eq!(state.gizmo_matrix(11, 1, &models, pivot, 0), dragged);
+        // Other ids are not affected.
+        assert_eq!(position(&state.gizmo_matrix(11, 2, &models, pivot, 0)), [2.0, 3.0, 0.0]);
+
+        state.end_frame(11, 1, false, &dragged);
+        assert_eq!(position(&state.gizmo_matrix(12, 1, &models, pivot, 0)), [2.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn drags_of_hidden_gizmos_are_forgotten() {
+        let mut models = models();
+        let models: Vec<&mut Matrix4> = models.iter_mut().collect();
+        let pivot = Pivot::BoundingBoxCenter;
+        let dragged = math::translation(&[9.0, 9.0, 9.0]);
+        let mut state = SelectionState::new();
+
+        // The gizmo is hidden while the drag ends and shown again later.
+        state.end_frame(10, 1, true, &dragged);
+        assert_eq!(position(&state.gizmo_matrix(20, 1, &models, pivot, 0)), [2.0, 3.0, 0.0]);
+
+        // Gizmos drawn in the same or the next frame keep each other's drags.
+        state.end_frame(20, 2, true, &dragged);
+        state.end_frame(20, 3, true, &dragged);
+        state.end_frame(21, 3, true, &dragged);
+        assert_eq!(state.drags.len(), 2);
+        assert!(!state.drags.contains_key(&1));
+    }
+}
@@ -0,0 +1,142 @@
+//! Snapping settings for every operation. See [`SnapConfig`].
+use crate::{AsMatrix4, Operation, Vector3};
+
+use imgui::{Key, Ui};
+
+/// Snapping steps for translations, rotations, scales and bounds. Pass it to
+/// [`Builder::with_snap_config`](crate::Builder::with_snap_config), which hands ImGuizmo the
+/// values of the active operation.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4, SnapConfig};
+/// # use imgui::Key;
+/// # let view = Matrix4::default();
+/// # let mut model = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let snap = SnapConfig::new()
+///     .with_enabled(false)
+///     .with_modifier(Key::ModCtrl)
+///     .with_rotation(15.0)
+///     .with_grid(&Matrix4::default());
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// gizmo.builder(&view, &mut model).with_snap_config(snap).manipulate();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SnapConfig {
+    /// Snapping is enabled. Defaults to `true`. Ignored, together with `modifier`, when the
+    /// builder also has a [`GizmoConfig`](crate::hotkeys::GizmoConfig), whose `snap` is used
+    /// instead.
+    pub enabled: bool,
+    /// A key that inverts `enabled` while held. Defaults to `None`.
+    pub modifier: Option<Key>,
+    /// The translation step along each axis. Defaults to `1.0`.
+    pub translation: Option<Vector3>,
+    /// Snap translations to a world space grid through this point instead of snapping the
+    /// distance moved. Only applies in `World` mode. Defaults to `None`.
+    pub translation_origin: Option<Vector3>,
+    /// The rotation step in degrees. Defaults to `15.0`.
+    pub rotation: Option<f32>,
+    /// The step of the scale ratio. Defaults to `0.1`.
+    pub scale: Option<f32>,
+    /// The step of the bounds along each axis. Defaults to `0.1`.
+    pub bounds: Option<Vector3>,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        SnapConfig {
+            enabled: true,
+            modifier: None,
+            translation: Some([1.0; 3]),
+            translation_origin: None,
+            rotation: Some(15.0),
+            scale: Some(0.1),
+            bounds: Some([0.1; 3]),
+        }
+    }
+}
+
+impl SnapConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn with_modifier<T: Into<Option<Key>>>(mut self, modifier: T) -> Self {
+        self.modifier = modifier.into();
+        self
+    }
+
+    pub fn with_translation<T: Into<Option<Vector3>>>(mut self, translation: T) -> Self {
+        self.translation = translation.into();
+        self
+    }
+
+    pub fn with_translation_origin<T: Into<Option<Vector3>>>(mut self, origin: T) -> Self {
+        self.translation_origin = origin.into();
+        self
+    }
+
+    pub fn with_rotation<T: Into<Option<f32>>>(mut self, degrees: T) -> Self {
+        self.rotation = degrees.into();
+        self
+    }
+
+    pub fn with_scale<T: Into<Option<f32>>>(mut self, scale: T) -> Self {
+        self.scale = scale.into();
+        self
+    }
+
+    pub fn with_bounds<T: Into<Option<Vector3>>>(mut self, bounds: T) -> Self {
+        self.bounds = bounds.into();
+        self
+    }
+
+    /// Snap translations to the lines of a grid drawn with `draw_grid` and the matrix `grid`.
+    /// The grid lines are one unit apart in the grid's space.
+    pub fn with_grid(mut self, grid: &impl AsMatrix4) -> Self {
+        let grid = grid.as_matrix4();
+        self.translation = Some([0, 1, 2].map(|axis| {
+            let [x, y, z, _] = grid[axis];
+            (x * x + y * y + z * z).sqrt()
+        }));
+        self.translation_origin = Some([grid[3][0], grid[3][1], grid[3][2]]);
+        self
+    }
+
+    /// Returns true if snapping is enabled, taking the modifier into account.
+    pub fn is_active(&self, ui: &Ui) -> bool {
+        let inverted = self.modifier.is_some_and(|key| ui.is_key_down(key));
+        self.enabled != inverted
+    }
+
+    /// The values ImGuizmo expects in its `snap` argument for `operation`.
+    pub(crate) fn values(&self, operation: Operation) -> Option<Vector3> {
+        match operation {
+            Operation::Translate => self.translation,
+            Operation::Rotate => self.rotation.map(|degrees| [degrees; 3]),
+            Operation::Scale => self.scale.map(|scale| [scale; 3]),
+            Operation::Bounds => None,
+        }
+    }
+
+    /// Snap the components of `translation` that differ from `before` to the world grid.
+    pub(crate) fn snap_to_grid(&self, before: &Vector3, translation: &mut Vector3) -> bool {
+        let (origin, step) = match (self.translation_origin, self.translation) {
+            (Some(origin), Some(step)) => (origin, step),
+            _ => return false,
+        };
+        for axis in 0..3 {
+            if translation[axis] != before[axis] && step[axis] > 0.0 {
+                let cells = ((translation[axis] - origin[axis]) / step[axis]).round();
+                translation[axis] = origin[axis] + cells * step[axis];
+            }
+        }
+        true
+    }
+}
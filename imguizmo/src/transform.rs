@@ -0,0 +1,353 @@
+use crate::decompose::{self, normalize_quaternion, Quaternion, Rotation, RotationFormat};
+use crate::{AsMatrix4, Matrix4, Operation, Vector3};
+
+use std::ops::Mul;
+
+/// A transformation stored as translation, rotation quaternion and scale, applied in
+/// scale, rotate, translate order.
+///
+/// [`Builder::manipulate_transform`](crate::Builder::manipulate_transform) edits a `Transform`
+/// in place, so that rotations are never round tripped through Euler angles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3,
+    pub rotation: Quaternion,
+    pub scale: Vector3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        translation: [0.0; 3],
+        rotation: [0.0, 0.0, 0.0, 1.0],
+        scale: [1.0; 3],
+    };
+
+    pub fn new(translation: Vector3, rotation: Quaternion, scale: Vector3) -> Self {
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Decompose `matrix`. A mirrored matrix is returned as a negative X scale.
+    pub fn from_matrix(matrix: &impl AsMatrix4) -> Self {
+        let components = decompose::decompose(matrix, RotationFormat::Quaternion);
+        Transform {
+            translation: components.translation,
+            rotation: components.rotation.to_quaternion(),
+            scale: components.scale,
+        }
+    }
+
+    /// Compose the column-major matrix `T * R * S`.
+    pub fn to_matrix(&self) -> Matrix4 {
+        let mut m: Matrix4 = Rotation::Quaternion(self.rotation).to_matrix();
+        for (column, scale) in m.iter_mut().zip(self.scale) {
+            for value in &mut column[..3] {
+                *value *= scale;
+            }
+        }
+        m[3] = [self.translation[0], self.translation[1], self.translation[2], 1.0];
+        m
+    }
+
+    /// The inverse transformation. Exact for uniform scales; with a non-uniform scale the
+    /// inverse of `T * R * S` is not a TRS transformation, and the rotation and scale are
+    /// inverted independently.
+    pub fn inverse(&self) -> Self {
+        let rotation = conjugate(self.rotation);
+        let scale = self.scale.map(|s| if s == 0.0 { 0.0 } else { 1.0 / s });
+        let translation = rotate(rotation, self.translation);
+        Transform {
+            translation: [
+                -translation[0] * scale[0],
+                -translation[1] * scale[1],
+                -translation[2] * scale[2],
+            ],
+            rotation,
+            scale,
+        }
+    }
+
+    /// Interpolate between `self` and `other`. Translation and scale are interpolated
+    /// linearly, the rotation along the shortest arc.
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+        Transform {
+            translation: lerp3(self.translation, other.translation, t),
+            rotation: slerp(self.rotation, other.rotation, t),
+            scale: lerp3(self.scale, other.scale, t),
+        }
+    }
+
+    /// Transform a point, i.e. scale, rotate and translate it.
+    pub fn transform_point(&self, point: Vector3) -> Vector3 {
+        let v = self.transform_vector(point);
+        [
+            v[0] + self.translation[0],
+            v[1] + self.translation[1],
+            v[2] + self.translation[2],
+        ]
+    }
+
+    /// Transform a direction, i.e. scale and rotate it.
+    pub fn transform_vector(&self, vector: Vector3) -> Vector3 {
+        rotate(
+            self.rotation,
+            [
+                vector[0] * self.scale[0],
+                vector[1] * self.scale[1],
+                vector[2] * self.scale[2],
+            ],
+        )
+    }
+
+    /// Apply a gizmo's result to this transform. Translations and rotations are taken from
+    /// the world-space `delta` matrix; scale and bounds edits from the scale of `model`.
+    pub(crate) fn apply_manipulation(&mut self, operation: Operation, delta: &Matrix4, model: &Matrix4) {
+        match operation {
+            Operation::Translate | Operation::Rotate => {
+                let [x, y, z] = self.translation;
+                for (row, value) in self.translation.iter_mut().enumerate() {
+                    *value = delta[0][row] * x + delta[1][row] * y + delta[2][row] * z + delta[3][row];
+                }
+                let rotation = Transform::from_matrix(delta).rotation;
+                self.rotation = normalize_quaternion(multiply(rotation, self.rotation));
+            }
+            Operation::Scale | Operation::Bounds => {
+                for (scale, column) in self.scale.iter_mut().zip(model) {
+                    let length = (column[0] * column[0] + column[1] * column[1] + column[2] * column[2]).sqrt();
+                    *scale = length.copysign(*scale);
+                }
+                if operation == Operation::Bounds {
+                    self.translation = [model[3][0], model[3][1], model[3][2]];
+                }
+            }
+        }
+    }
+}
+
+/// `self * other` applies `other` first, like the product of their matrices. Exact unless
+/// `self` has a non-uniform scale and `other` a rotation.
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(other.translation),
+            rotation: normalize_quaternion(multiply(self.rotation, other.rotation)),
+            scale: [
+                self.scale[0] * other.scale[0],
+                self.scale[1] * other.scale[1],
+                self.scale[2] * other.scale[2],
+            ],
+        }
+    }
+}
+
+impl From<Matrix4> for Transform {
+    fn from(matrix: Matrix4) -> Self {
+        Transform::from_matrix(&matrix)
+    }
+}
+
+impl From<Transform> for Matrix4 {
+    fn from(transform: Transform) -> Self {
+        transform.to_matrix()
+    }
+}
+
+fn conjugate(q: Quaternion) -> Quaternion {
+    [-q[0], -q[1], -q[2], q[3]]
+}
+
+/// Hamilton product `a * b`, i.e. `b` is applied first.
+fn multiply(a: Quaternion, b: Quaternion) -> Quaternion {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+fn rotate(q: Quaternion, v: Vector3) -> Vector3 {
+    let [x, y, z, _] = multiply(multiply(q, [v[0], v[1], v[2], 0.0]), conjugate(q));
+    [x, y, z]
+}
+
+fn slerp(a: Quaternion, b: Quaternion, t: f32) -> Quaternion {
+    let mut cos = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    let b = if cos < 0.0 {
+        cos = -cos;
+        b.map(|v| -v)
+    } else {
+        b
+    };
+    let (wa, wb) = if cos > 0.9995 {
+        // Nearly parallel, fall back to a normalized lerp.
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+    normalize_quaternion([
+        a[0] * wa + b[0] * wb,
+        a[1] * wa + b[1] * wb,
+        a[2] * wa + b[2] * wb,
+        a[3] * wa + b[3] * wb,
+    ])
+}
+
+fn lerp3(a: Vector3, b: Vector3, t: f32) -> Vector3 {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math;
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-4, "{a:?} != {b:?}");
+        }
+    }
+
+    fn assert_matrix_close(a: &Matrix4, b: &Matrix4) {
+        assert_close(a.as_flattened(), b.as_flattened());
+    }
+
+    /// Compares rotations, which `q` and `-q` both describe.
+    fn assert_rotation_close(a: Quaternion, b: Quaternion) {
+        let sign = if a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>() < 0.0 { -1.0 } else { 1.0 };
+        assert_close(&a, &b.map(|v| v * sign));
+    }
+
+    fn axis_angle(axis: Vector3, degrees: f32) -> Quaternion {
+        let (sin, cos) = (degrees.to_radians() / 2.0).sin_cos();
+        [axis[0] * sin, axis[1] * sin, axis[2] * sin, cos]
+    }
+
+    fn sample() -> Transform {
+        let length = (1.0f32 + 4.0 + 9.0).sqrt();
+        Transform::new(
+            [1.0, -2.0, 3.5],
+            axis_angle([1.0 / length, 2.0 / length, 3.0 / length], 70.0),
+            [2.0, 0.5, 1.5],
+        )
+    }
+
+    #[test]
+    fn matrix_round_trips() {
+        let transform = sample();
+        let matrix = transform.to_matrix();
+        let decomposed = Transform::from_matrix(&matrix);
+        assert_close(&decomposed.translation, &transform.translation);
+        assert_rotation_close(decomposed.rotation, transform.rotation);
+        assert_close(&decomposed.scale, &transform.scale);
+        assert_matrix_close(&decomposed.to_matrix(), &matrix);
+
+        let point = [0.3, -1.0, 2.0];
+        let expected: Vector3 = math::transform_point(&matrix, &point);
+        assert_close(&transform.transform_point(point), &expected);
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = Transform {
+            scale: [2.0; 3],
+            ..sample()
+        };
+        for identity in [transform * transform.inverse(), transform.inverse() * transform] {
+            assert_matrix_close(&identity.to_matrix(), &math::IDENTITY);
+        }
+        let point = [0.3, -1.0, 2.0];
+        assert_close(&transform.inverse().transform_point(transform.transform_point(point)), &point);
+        let inverse: Matrix4 = math::inverse(&transform.to_matrix()).unwrap();
+        assert_matrix_close(&transform.inverse().to_matrix(), &inverse);
+    }
+
+    #[test]
+    fn lerp_hits_its_endpoints() {
+        let a = sample();
+        let b = Transform::new([-4.0, 0.0, 1.0], axis_angle([0.0, 1.0, 0.0], -120.0), [1.0; 3]);
+        for (t, expected) in [(0.0, a), (1.0, b)] {
+            let lerped = a.lerp(&b, t);
+            assert_close(&lerped.translation, &expected.translation);
+            assert_rotation_close(lerped.rotation, expected.rotation);
+            assert_close(&lerped.scale, &expected.scale);
+        }
+
+        let half = Transform::IDENTITY.lerp(&b, 0.5);
+        assert_close(&half.translation, &[-2.0, 0.0, 0.5]);
+        assert_rotation_close(half.rotation, axis_angle([0.0, 1.0, 0.0], -60.0));
+        // `-q` is the same rotation, and slerp takes the shortest arc to it.
+        let negated = Transform {
+            rotation: b.rotation.map(|v| -v),
+            ..b
+        };
+        assert_rotation_close(Transform::IDENTITY.lerp(&negated, 0.5).rotation, half.rotation);
+    }
+
+    #[test]
+    fn mul_matches_the_matrix_product() {
+        let a = Transform {
+            scale: [1.5; 3],
+            ..sample()
+        };
+        let b = Transform::new([0.0, 1.0, -1.0], axis_angle([0.0, 0.0, 1.0], 30.0), [1.0, 2.0, 3.0]);
+        let product: Matrix4 = math::multiply(&a.to_matrix(), &b.to_matrix());
+        assert_matrix_close(&(a * b).to_matrix(), &product);
+        assert_eq!(a * Transform::IDENTITY, a);
+    }
+
+    #[test]
+    fn manipulations_are_applied_without_drift() {
+        let mut transform = sample();
+        let translate = math::translation(&[1.0, 2.0, 3.0]);
+        let model: Matrix4 = math::multiply(&translate, &transform.to_matrix());
+        transform.apply_manipulation(Operation::Translate, &translate, &model);
+        assert_close(&transform.translation, &[2.0, 0.0, 6.5]);
+        assert_rotation_close(transform.rotation, sample().rotation);
+        assert_eq!(transform.scale, sample().scale);
+
+        // Rotations around the world Y axis, which also move the translation around it.
+        let mut transform = sample();
+        let step = math::rotation_axis(1, 10f32.to_radians());
+        for _ in 0..36 {
+            let model: Matrix4 = math::multiply(&step, &transform.to_matrix());
+            transform.apply_manipulation(Operation::Rotate, &step, &model);
+        }
+        assert_close(&transform.translation, &sample().translation);
+        assert_rotation_close(transform.rotation, sample().rotation);
+        assert_eq!(transform.scale, sample().scale);
+
+        // Scales come from the columns of the model and keep their sign.
+        let mut transform = Transform {
+            scale: [-2.0, 0.5, 1.5],
+            ..sample()
+        };
+        let mut model = transform.to_matrix();
+        for value in &mut model[1][..3] {
+            *value *= 3.0;
+        }
+        transform.apply_manipulation(Operation::Scale, &math::IDENTITY, &model);
+        assert_close(&transform.scale, &[-2.0, 1.5, 1.5]);
+        assert_eq!(transform.translation, sample().translation);
+        assert_eq!(transform.rotation, sample().rotation);
+    }
+}
@@ -0,0 +1,149 @@
+//! Validation of the matrices passed to `ImGuizmo::Manipulate`.
+//!
+//! ImGuizmo does not check its inputs: a `NaN`, a model matrix with a zero scale or a singular
+//! view matrix produce garbage or permanently `NaN` model matrices. Validation is opt-in, see
+//! [`Gizmo::set_validation`](crate::Gizmo::set_validation),
+//! [`Builder::with_validation`](crate::Builder::with_validation) and the `try_manipulate`
+//! methods.
+use crate::{math, AsMatrix4, Matrix4, Rect};
+
+use std::error::Error;
+use std::fmt;
+
+/// How the inputs of `manipulate` are validated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Validation {
+    /// Pass the inputs to ImGuizmo unchecked.
+    #[default]
+    None,
+    /// Skip the gizmo when the inputs are invalid. `manipulate` returns false.
+    Skip,
+    /// Like `Skip`, but panic with the `ValidationError` in debug builds.
+    DebugAssert,
+}
+
+impl Validation {
+    /// `Skip` if validation is disabled.
+    pub(crate) fn or_skip(self) -> Self {
+        match self {
+            Validation::None => Validation::Skip,
+            validation => validation,
+        }
+    }
+
+    /// Run `validate` unless validation is disabled.
+    pub(crate) fn check(
+        self,
+        validate: impl FnOnce() -> Result<(), ValidationError>,
+    ) -> Result<(), ValidationError> {
+        match self {
+            Validation::None => Ok(()),
+            Validation::Skip => validate(),
+            Validation::DebugAssert => {
+                let result = validate();
+                if let Err(error) = &result {
+                    debug_assert!(false, "invalid gizmo input: {}", error);
+                }
+                result
+            }
+        }
+    }
+}
+
+/// A matrix passed to `manipulate`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    View,
+    Projection,
+    Model,
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Input::View => "view",
+            Input::Projection => "projection",
+            Input::Model => "model",
+        })
+    }
+}
+
+/// The reason the inputs of `manipulate` were rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    /// The matrix contains a `NaN` or an infinite value.
+    NotFinite(Input),
+    /// The view matrix cannot be inverted.
+    SingularView,
+    /// The model matrix cannot be inverted, e.g. because of a zero scale.
+    SingularModel,
+    /// The parent matrix set with `Builder::with_parent` cannot be inverted.
+    SingularParent,
+    /// The projection matrix cannot be inverted or does not project onto the viewport.
+    DegenerateProjection,
+    /// The viewport `Rect` has no area.
+    EmptyRect,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::NotFinite(input) => {
+                write!(f, "the {} matrix contains a NaN or infinite value", input)
+            }
+            ValidationError::SingularView => f.write_str("the view matrix is not invertible"),
+            ValidationError::SingularModel => f.write_str("the model matrix is not invertible"),
+            ValidationError::SingularParent => f.write_str("the parent matrix is not invertible"),
+            ValidationError::DegenerateProjection => {
+                f.write_str("the projection matrix is degenerate")
+            }
+            ValidationError::EmptyRect => f.write_str("the viewport rect is empty"),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Check the inputs of `manipulate`. The `rect` is only checked when given.
+pub fn validate(
+    view: &impl AsMatrix4,
+    projection: &impl AsMatrix4,
+    model: &impl AsMatrix4,
+    rect: Option<&Rect>,
+) -> Result<(), ValidationError> {
+    let (view, projection, model) = (view.as_matrix4(), projection.as_matrix4(), model.as_matrix4());
+    for (matrix, input) in [
+        (view, Input::View),
+        (projection, Input::Projection),
+        (model, Input::Model),
+    ] {
+        if !is_finite(matrix) {
+            return Err(ValidationError::NotFinite(input));
+        }
+    }
+
+    if math::affine_inverse::<Matrix4>(view).is_none() {
+        return Err(ValidationError::SingularView);
+    }
+    if math::affine_inverse::<Matrix4>(model).is_none() {
+        return Err(ValidationError::SingularModel);
+    }
+    if projection[0][0] == 0.0
+        || projection[1][1] == 0.0
+        || math::inverse::<Matrix4>(projection).is_none()
+    {
+        return Err(ValidationError::DegenerateProjection);
+    }
+
+    if let Some(rect) = rect {
+        let valid = |v: f32| v.is_finite() && v > 0.0;
+        if !valid(rect.width) || !valid(rect.height) || !rect.x.is_finite() || !rect.y.is_finite() {
+            return Err(ValidationError::EmptyRect);
+        }
+    }
+    Ok(())
+}
+
+fn is_finite(matrix: &Matrix4) -> bool {
+    matrix.iter().flatten().all(|v| v.is_finite())
+}
@@ -0,0 +1,220 @@
+use crate::{
+    draw_cube, draw_grid, ffi, manipulate, with_clip_rect, without_mouse, AsMatrix4, Builder,
+    DrawTarget, Gizmo, Matrix4, Mode, Projection, Ray, Rect, Validation, ValidationError,
+    Vector2, Vector3,
+};
+
+use std::ptr;
+
+/// Per-frame viewport state shared by many builders, grids, cubes and view cubes.
+///
+/// The projection matrix is computed once and the rect, orthographic flag and draw target are
+/// applied to ImGuizmo once, when the viewport is created.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4, Projection, Rect};
+/// # let view = Matrix4::default();
+/// # let mut a = Matrix4::default();
+/// # let mut b = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let gizmo = Gizmo::begin_frame(ui);
+/// let viewport = gizmo.viewport(Rect::from_display(ui), &view, Projection::Perspective { fovy: 45.0 });
+///
+/// viewport.draw_grid(&Matrix4::default(), 10.0);
+/// viewport.builder(&mut a).manipulate();
+/// viewport.builder(&mut b).manipulate();
+/// ```
+pub struct Viewport<'a> {
+    gizmo: &'a Gizmo<'a>,
+    rect: Rect,
+    view: Matrix4,
+    projection: Projection,
+    projection_matrix: Matrix4,
+    draw_target: Option<DrawTarget<'a>>,
+    clip: bool,
+}
+
+impl<'a> Viewport<'a> {
+    pub fn new(gizmo: &'a Gizmo<'a>, rect: Rect, view: &impl AsMatrix4, projection: Projection) -> Viewport<'a> {
+        let viewport = Viewport {
+            gizmo,
+            rect,
+            view: *view.as_matrix4(),
+            projection,
+            projection_matrix: projection.matrix(&rect, gizmo.framebuffer_scale()),
+            draw_target: None,
+            clip: false,
+        };
+        viewport.apply();
+        viewport
+    }
+
+    /// Set the draw list to render into. Defaults to leaving the current draw list untouched,
+    /// which is ImGuizmo's own full-screen overlay unless another draw list was set, or to the
+    /// background draw list when clipping.
+    pub fn with_draw_target<T: Into<Option<DrawTarget<'a>>>>(mut self, draw_target: T) -> Self {
+        self.draw_target = draw_target.into();
+        if self.clip && self.draw_target.is_none() {
+            // ImGuizmo's own overlay is not exposed, so it cannot be clipped.
+            self.draw_target = Some(DrawTarget::Background(None));
+        }
+        if let Some(target) = self.draw_target {
+            self.gizmo.set_draw_target(target);
+        }
+        self
+    }
+
+    /// Set to `true` to clip drawing and hover detection to the viewport `Rect`. Without a
+    /// draw target, this draws into the background draw list of the current window's viewport
+    /// instead of ImGuizmo's own overlay. Defaults to `false`.
+    pub fn with_clip(mut self, clip: bool) -> Self {
+        self.clip = clip;
+        let draw_target = self.draw_target;
+        self.with_draw_target(draw_target)
+    }
+
+    /// Apply this viewport's state to ImGuizmo again. Only needed when another viewport was
+    /// used since this one was created.
+    pub fn apply(&self) {
+        if let Some(target) = self.draw_target {
+            self.gizmo.set_draw_target(target);
+        }
+        self.gizmo.set_orthographic(self.projection.is_orthographic());
+        self.gizmo
+            .set_rect(self.rect.x, self.rect.y, self.rect.width, self.rect.height);
+    }
+
+    /// Configure a gizmo for manipulating `model` in this viewport.
+    pub fn builder<'b, M: AsMatrix4>(&'b self, model: &'b mut M) -> Builder<'b> {
+        Builder::new(self.gizmo, &self.view, model).with_viewport(self)
+    }
+
+    /// Configure a gizmo without a model matrix in this viewport, e.g. for
+    /// [`Builder::manipulate_transform`].
+    pub fn view_builder(&self) -> Builder<'_> {
+        Builder::from_view(self.gizmo, &self.view).with_viewport(self)
+    }
+
+    pub fn gizmo(&self) -> &'a Gizmo<'a> {
+        self.gizmo
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn view(&self) -> &Matrix4 {
+        &self.view
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn projection_matrix(&self) -> &Matrix4 {
+        &self.projection_matrix
+    }
+
+    pub fn draw_target(&self) -> Option<DrawTarget<'a>> {
+        self.draw_target
+    }
+
+    /// The world space ray under the mouse, or `None` if the view or projection is singular.
+    pub fn mouse_ray(&self) -> Option<Ray> {
+        self.ray(self.gizmo.ui().io().mouse_pos)
+    }
+
+    /// The world space ray through the screen `point`.
+    pub fn ray(&self, point: Vector2) -> Option<Ray> {
+        Ray::from_screen(point, &self.rect, &self.view, &self.projection_matrix)
+    }
+
+    /// Draw a grid for debugging.
+    pub fn draw_grid(&self, model: &impl AsMatrix4, grid_size: f32) {
+        with_clip_rect(self.clip_draw_list(), &self.rect, || {
+            draw_grid(self.gizmo, &self.view, &self.projection_matrix, model.as_matrix4(), grid_size)
+        });
+    }
+
+    /// Draw a cube for debugging with `manipulate`.
+    pub fn draw_cube(&self, model: &impl AsMatrix4) {
+        with_clip_rect(self.clip_draw_list(), &self.rect, || {
+            draw_cube(self.gizmo, &self.view, &self.projection_matrix, model.as_matrix4())
+        });
+    }
+
+    /// Draw a cube for each of the `models`.
+    pub fn draw_cubes(&self, models: &[Matrix4]) {
+        with_clip_rect(self.clip_draw_list(), &self.rect, || unsafe {
+            ffi::ImGuizmo_DrawCubes(
+                self.view.as_ptr() as *const f32,
+                self.projection_matrix.as_ptr() as *const f32,
+                models.as_ptr() as *const f32,
+                models.len() as i32,
+            );
+        });
+    }
+
+    /// Draw a view cube of the given `size` in the top right corner of the viewport.
+    pub fn view_manipulate(
+        &self,
+        view: &mut impl AsMatrix4,
+        camera_distance: f32,
+        size: Vector2,
+        background_color: u32,
+    ) {
+        let position = [self.rect.x + self.rect.width - size[0], self.rect.y];
+        self.gizmo
+            .view_manipulate(view, camera_distance, position, size, background_color);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn manipulate(
+        &self,
+        view: &Matrix4,
+        operation: i32,
+        mode: Mode,
+        model: &mut Matrix4,
+        delta_matrix: Option<&mut Matrix4>,
+        snap: Option<&mut Vector3>,
+        local_bounds: Option<&mut [Vector3; 2]>,
+        bounds_snap: Option<&mut Vector3>,
+        validation: Validation,
+    ) -> Result<bool, ValidationError> {
+        validation.check(|| {
+            crate::validation::validate(view, &self.projection_matrix, model, Some(&self.rect))
+        })?;
+        let hide_mouse = self.clip
+            && !self.rect.contains(self.gizmo.ui().io().mouse_pos)
+            && !self.gizmo.is_using();
+        Ok(with_clip_rect(self.clip_draw_list(), &self.rect, || {
+            let manipulate = || {
+                manipulate(
+                    self.gizmo,
+                    view,
+                    &self.projection_matrix,
+                    operation,
+                    mode,
+                    model,
+                    delta_matrix,
+                    snap,
+                    local_bounds,
+                    bounds_snap,
+                )
+            };
+            if hide_mouse {
+                without_mouse(manipulate)
+            } else {
+                manipulate()
+            }
+        }))
+    }
+
+    /// The draw list to clip, or null when clipping is disabled.
+    fn clip_draw_list(&self) -> *mut imgui::sys::ImDrawList {
+        match self.draw_target {
+            Some(target) if self.clip => target.as_ptr(),
+            _ => ptr::null_mut(),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Editor widgets that edit the same matrices as the gizmo.
+use crate::decompose::{self, AngleUnit, EulerOrder, Rotation, RotationFormat};
+use crate::{AsMatrix4, Matrix4, Vector3};
+
+use imgui::{Drag, Ui};
+
+const ORDERS: [EulerOrder; 6] = [
+    EulerOrder::XYZ,
+    EulerOrder::XZY,
+    EulerOrder::YXZ,
+    EulerOrder::YZX,
+    EulerOrder::ZXY,
+    EulerOrder::ZYX,
+];
+const ORDER_NAMES: [&str; 6] = ["XYZ", "XZY", "YXZ", "YZX", "ZXY", "ZYX"];
+const UNITS: [AngleUnit; 2] = [AngleUnit::Degrees, AngleUnit::Radians];
+const UNIT_NAMES: [&str; 2] = ["Degrees", "Radians"];
+
+/// The settings of a [`transform_inspector`] and the state it keeps between frames. Keep one
+/// alive for each inspected matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InspectorState {
+    /// The order of the Euler angles shown. Defaults to `XYZ`, like ImGuizmo.
+    pub order: EulerOrder,
+    /// The unit of the Euler angles shown. Defaults to `Degrees`.
+    pub unit: AngleUnit,
+    /// Scale all axes by the same ratio when one of them is edited. Defaults to `false`.
+    pub uniform_scale: bool,
+    /// The change of the translation per pixel dragged. Defaults to `0.01`.
+    pub translation_speed: f32,
+    /// The change of the rotation in degrees per pixel dragged, whatever the unit shown.
+    /// Defaults to `0.5`.
+    pub rotation_speed: f32,
+    /// The change of the scale per pixel dragged. Defaults to `0.01`.
+    pub scale_speed: f32,
+    /// The angles shown last and the matrix they were shown for. Decomposing the matrix again
+    /// would make the angles jump when they wrap around or near gimbal lock.
+    angles: Option<(Matrix4, EulerOrder, AngleUnit, Vector3)>,
+}
+
+impl Default for InspectorState {
+    fn default() -> Self {
+        InspectorState {
+            order: EulerOrder::XYZ,
+            unit: AngleUnit::Degrees,
+            uniform_scale: false,
+            translation_speed: 0.01,
+            rotation_speed: 0.5,
+            scale_speed: 0.01,
+            angles: None,
+        }
+    }
+}
+
+impl InspectorState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_order(mut self, order: EulerOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_unit(mut self, unit: AngleUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn with_uniform_scale(mut self, uniform_scale: bool) -> Self {
+        self.uniform_scale = uniform_scale;
+        self
+    }
+
+    pub fn with_speeds(mut self, translation: f32, rotation_degrees: f32, scale: f32) -> Self {
+        self.translation_speed = translation;
+        self.rotation_speed = rotation_degrees;
+        self.scale_speed = scale;
+        self
+    }
+}
+
+/// Draw drag fields for the translation, Euler rotation and scale of `matrix`, with reset
+/// buttons, a choice of Euler order and unit, a uniform scale lock and copy and paste of the
+/// whole matrix through the clipboard. Returns true if `matrix` was changed.
+///
+/// The matrix is decomposed with the [`decompose`](crate::decompose) module, so editing it
+/// removes any shear.
+///
+/// ```rust,no_run
+/// # use imguizmo::{Gizmo, Matrix4};
+/// # use imguizmo::widgets::{transform_inspector, InspectorState};
+/// # let view = Matrix4::default();
+/// # let mut model = Matrix4::default();
+/// # let ui = unsafe { std::mem::uninitialized() };
+/// let mut state = InspectorState::new();
+///
+/// let gizmo = Gizmo::begin_frame(ui);
+/// gizmo.builder(&view, &mut model).manipulate();
+/// transform_inspector(ui, &mut model, &mut state);
+/// ```
+pub fn transform_inspector(ui: &Ui, matrix: &mut impl AsMatrix4, state: &mut InspectorState) -> bool {
+    let _id = ui.push_id_ptr(state);
+    let matrix = matrix.as_matrix4_mut();
+    // The angles are edited in the order and unit chosen before this frame.
+    let (order, unit) = (state.order, state.unit);
+    let components = decompose::decompose(matrix, RotationFormat::Euler(order, unit));
+    let (mut translation, mut scale) = (components.translation, components.scale);
+    let mut angles = match (state.angles, components.rotation) {
+        (Some((shown, shown_order, shown_unit, angles)), _)
+            if shown == *matrix && shown_order == order && shown_unit == unit =>
+        {
+            angles
+        }
+        (_, Rotation::Euler { angles, .. }) => angles,
+        _ => [0.0; 3],
+    };
+
+    let translation_speed = state.translation_speed;
+    let translated = drag_row(ui, "Translation", &mut translation, translation_speed, [0.0; 3], false);
+    let rotation_speed = unit.from_radians(state.rotation_speed.to_radians());
+    let rotated = drag_row(ui, "Rotation", &mut angles, rotation_speed, [0.0; 3], false);
+    let uniform_scale = state.uniform_scale;
+    let scaled = drag_row(ui, "Scale", &mut scale, state.scale_speed, [1.0; 3], uniform_scale);
+
+    if let Some(order) = combo(ui, "Euler order", &ORDER_NAMES, &ORDERS, state.order) {
+        state.order = order;
+    }
+    if let Some(unit) = combo(ui, "Unit", &UNIT_NAMES, &UNITS, state.unit) {
+        state.unit = unit;
+    }
+    ui.checkbox("Uniform scale", &mut state.uniform_scale);
+
+    if ui.button("Copy") {
+        ui.set_clipboard_text(to_text(matrix));
+    }
+    ui.same_line();
+    let pasted = ui.button("Paste")
+        && match ui.clipboard_text().as_deref().and_then(from_text) {
+            Some(pasted) => {
+                *matrix = pasted;
+                true
+            }
+            None => false,
+        };
+
+    let changed = if pasted {
+        state.angles = None;
+        true
+    } else if rotated || scaled {
+        let rotation = Rotation::Euler { angles, order, unit };
+        *matrix = decompose::recompose(&translation, &rotation, &scale);
+        true
+    } else if translated {
+        // Leave the rest of the matrix untouched.
+        matrix[3] = [translation[0], translation[1], translation[2], 1.0];
+        true
+    } else {
+        false
+    };
+    // If the order or unit was changed, the angles are decomposed again next frame.
+    if !pasted {
+        state.angles = Some((*matrix, order, unit, angles));
+    }
+    changed
+}
+
+/// A drag field for three values with a button that resets them to `reset`. When `uniform`,
+/// dragging one value scales the others by the same ratio.
+fn drag_row(ui: &Ui, label: &str, values: &mut Vector3, speed: f32, reset: Vector3, uniform: bool) -> bool {
+    let before = *values;
+    let mut changed = Drag::new(label).speed(speed).build_array(ui, values);
+    if changed && uniform {
+        changed = scale_uniformly(&before, values);
+    }
+    ui.same_line();
+    if ui.small_button(format!("Reset##{}", label)) && *values != reset {
+        *values = reset;
+        changed = true;
+    }
+    changed
+}
+
+/// A combo box choosing one of `values` by its name. Returns the new value if one was chosen.
+fn combo<T: Copy + PartialEq>(ui: &Ui, label: &str, names: &[&str], values: &[T], current: T) -> Option<T> {
+    let mut index = values.iter().position(|value| *value == current).unwrap_or(0);
+    if ui.combo_simple_string(label, &mut index, names) && values[index] != current {
+        Some(values[index])
+    } else {
+        None
+    }
+}
+
+/// Scale all axes of `scale` by the ratio of the axis that was changed from `before`.
+/// Returns true if `scale` differs from `before`.
+fn scale_uniformly(before: &Vector3, scale: &mut Vector3) -> bool {
+    let axis = match (0..3).find(|&axis| scale[axis] != before[axis]) {
+        Some(axis) => axis,
+        None => return false,
+    };
+    if before[axis].abs() > f32::EPSILON {
+        let ratio = scale[axis] / before[axis];
+        *scale = before.map(|value| value * ratio);
+    } else {
+        *scale = [scale[axis]; 3];
+    }
+    true
+}
+
+/// The matrix as 16 comma-separated numbers in column-major order.
+fn to_text(matrix: &Matrix4) -> String {
+    matrix
+        .iter()
+        .flatten()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse 16 numbers in column-major order, separated by commas or whitespace and optionally
+/// wrapped in brackets.
+fn from_text(text: &str) -> Option<Matrix4> {
+    let values = text
+        .split(|c: char| c == ',' || c == '[' || c == ']' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+    if values.len() != 16 {
+        return None;
+    }
+    let mut matrix = [[0.0; 4]; 4];
+    for (value, component) in values.into_iter().zip(matrix.iter_mut().flatten()) {
+        *component = value;
+    }
+    Some(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATRIX: Matrix4 = [
+        [0.1, -2.0, 3.5, 0.0],
+        [1e-7, 1.0, -0.0, 0.0],
+        [4.0, 5.25, 6.0, 0.0],
+        [-1e6, 7.0, 8.0, 1.0],
+    ];
+
+    #[test]
+    fn text_round_trips() {
+        let text = to_text(&MATRIX);
+        assert!(text.starts_with("0.1, -2, 3.5, 0, 0.0000001, 1"), "{text}");
+        assert_eq!(from_text(&text), Some(MATRIX));
+        assert_eq!(from_text(&to_text(&crate::math::IDENTITY)), Some(crate::math::IDENTITY));
+    }
+
+    #[test]
+    fn text_accepts_brackets_and_whitespace() {
+        let text = "[[1, 0, 0, 0],\n [0, 1, 0, 0],\n [0, 0, 1, 0],\n [2 3\t4 1]]";
+        let mut expected = crate::math::IDENTITY;
+        expected[3] = [2.0, 3.0, 4.0, 1.0];
+        assert_eq!(from_text(text), Some(expected));
+    }
+
+    #[test]
+    fn malformed_text_is_rejected() {
+        let fifteen = vec!["1"; 15].join(", ");
+        let seventeen = vec!["1"; 17].join(", ");
+        for text in [
+            "",
+            "[]",
+            fifteen.as_str(),
+            seventeen.as_str(),
+            &format!("{fifteen}, x"),
+            &format!("{fifteen}, NaN"),
+            &format!("{fifteen}, inf"),
+            &format!("{fifteen}; 1"),
+        ] {
+            assert_eq!(from_text(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn uniform_scale_keeps_ratios() {
+        let mut scale = [3.0, 2.0, 4.0];
+        assert!(scale_uniformly(&[1.5, 1.0, 2.0], &mut scale));
+        assert_eq!(scale, [3.0, 2.0, 4.0]);
+
+        let mut scale = [1.0, 0.5, 2.0];
+        assert!(!scale_uniformly(&[1.0, 0.5, 2.0], &mut scale));
+        assert_eq!(scale, [1.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn uniform_scale_from_a_zero_component() {
+        // A ratio to zero is undefined, so all axes take the new value.
+        let mut scale = [2.0, 1.0, 3.0];
+        assert!(scale_uniformly(&[0.0, 1.0, 3.0], &mut scale));
+        assert_eq!(scale, [2.0; 3]);
+
+        // Other zero axes stay zero when a non-zero axis is scaled.
+        let mut scale = [0.0, 2.0, 0.0];
+        assert!(scale_uniformly(&[0.0, 1.0, 0.0], &mut scale));
+        assert_eq!(scale, [0.0, 2.0, 0.0]);
+
+        // Scaling to zero collapses all axes.
+        let mut scale = [0.0, 1.0, 3.0];
+        assert!(scale_uniformly(&[2.0, 1.0, 3.0], &mut scale));
+        assert_eq!(scale, [0.0; 3]);
+    }
+}